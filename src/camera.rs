@@ -0,0 +1,204 @@
+use rltk::{ Rltk, RGB, Point };
+use specs::prelude::*;
+use super::{ Map, TileType, Position, Renderable, Hidden };
+
+/// The part of the screen the map is drawn in, above the UI box
+pub const VIEW_WIDTH : i32 = 80;
+pub const VIEW_HEIGHT : i32 = 43;
+
+/// Which part of the map is on screen: the map tile drawn in the top left
+/// corner of the view
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Viewport {
+    pub min_x : i32,
+    pub min_y : i32,
+}
+
+impl Viewport {
+    /// Centres the view on `focus`, without scrolling past the edges of a map
+    /// larger than the screen
+    pub fn centred_on(map : &Map, focus : Point) -> Viewport {
+        Viewport{
+            min_x : (focus.x - VIEW_WIDTH / 2).clamp(0, i32::max(0, map.width - VIEW_WIDTH)),
+            min_y : (focus.y - VIEW_HEIGHT / 2).clamp(0, i32::max(0, map.height - VIEW_HEIGHT)),
+        }
+    }
+
+    /// The view the player sees
+    pub fn for_player(ecs : &World) -> Viewport {
+        Viewport::centred_on(&ecs.fetch::<Map>(), *ecs.fetch::<Point>())
+    }
+
+    pub fn world_to_screen(&self, world : Point) -> Point {
+        Point::new(world.x - self.min_x, world.y - self.min_y)
+    }
+
+    /// The map tile under a screen cell, if the cell is inside the view
+    pub fn screen_to_world(&self, screen : Point) -> Option<Point> {
+        if screen.x < 0 || screen.x >= VIEW_WIDTH || screen.y < 0 || screen.y >= VIEW_HEIGHT { return None; }
+        Some(Point::new(screen.x + self.min_x, screen.y + self.min_y))
+    }
+}
+
+/// Draws the part of the map around the player, then everything standing on it
+pub fn render_camera(ecs : &World, ctx : &mut Rltk) {
+    let map = ecs.fetch::<Map>();
+    let view = Viewport::for_player(ecs);
+    draw_map(&map, view, ctx);
+
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mut data = (&positions, &renderables, !&hidden).join().collect::<Vec<_>>();
+    data.sort_by( |&a, &b| b.1.render_order.cmp(&a.1.render_order) );
+    for (pos, render, _hidden) in data.iter() {
+        if pos.x < 0 || pos.x >= map.width || pos.y < 0 || pos.y >= map.height { continue; }
+        let idx = map.xy_idx(pos.x, pos.y);
+        let screen = view.world_to_screen(Point::new(pos.x, pos.y));
+        if map.visible_tiles[idx] && view.screen_to_world(screen).is_some() {
+            ctx.set(screen.x, screen.y, render.fg, render.bg, render.glyph);
+        }
+    }
+}
+
+/// Draws a whole map for the mapgen visualizer, centred if it's bigger than
+/// the screen
+pub fn render_debug_map(map : &Map, ctx : &mut Rltk) {
+    let view = Viewport::centred_on(map, Point::new(map.width / 2, map.height / 2));
+    draw_map(map, view, ctx);
+}
+
+fn draw_map(map : &Map, view : Viewport, ctx : &mut Rltk) {
+    for screen_y in 0..VIEW_HEIGHT {
+        for screen_x in 0..VIEW_WIDTH {
+            let (x, y) = (screen_x + view.min_x, screen_y + view.min_y);
+            if x >= map.width || y >= map.height { continue; }
+            let idx = map.xy_idx(x, y);
+            if map.revealed_tiles[idx] {
+                let (glyph, fg, bg) = tile_glyph(idx, map);
+                ctx.set(screen_x, screen_y, fg, bg, glyph);
+            }
+        }
+    }
+}
+
+/// How a map tile is drawn: greyed out unless it's in view, with walls
+/// joined up to their revealed neighbours
+pub fn tile_glyph(idx : usize, map : &Map) -> (rltk::FontCharType, RGB, RGB) {
+    let (x, y) = (idx as i32 % map.width, idx as i32 / map.width);
+    let mut glyph;
+    let mut fg;
+    let mut bg = RGB::named(rltk::BLACK);
+
+    match map.tiles[idx] {
+        TileType::Floor => {
+            glyph = rltk::to_cp437('·');
+            fg = RGB::from_f32(0.0, 0.5, 0.5);
+        }
+        TileType::Wall => {
+            glyph = wall_glyph(map, x, y);
+            fg = RGB::named(rltk::VIOLET);
+        },
+        TileType::DownStairs => {
+            glyph = rltk::to_cp437('»');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
+        TileType::UpStairs => {
+            glyph = rltk::to_cp437('«');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
+        TileType::Road => {
+            glyph = rltk::to_cp437('≡');
+            fg = RGB::named(rltk::GRAY);
+        }
+        TileType::Grass => {
+            glyph = rltk::to_cp437('"');
+            fg = RGB::named(rltk::GREEN);
+        }
+        TileType::Gravel => {
+            glyph = rltk::to_cp437(';');
+            fg = RGB::from_f32(0.5, 0.5, 0.5);
+        }
+        TileType::WoodFloor => {
+            glyph = rltk::to_cp437('░');
+            fg = RGB::named(rltk::CHOCOLATE);
+        }
+        TileType::Bridge => {
+            glyph = rltk::to_cp437('.');
+            fg = RGB::named(rltk::CHOCOLATE);
+        }
+        TileType::ShallowWater => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::named(rltk::CYAN);
+        }
+        TileType::DeepWater => {
+            glyph = rltk::to_cp437('~');
+            fg = RGB::named(rltk::BLUE);
+        }
+    }
+    if map.bloodstains.contains(&idx) {
+        fg = RGB::named(rltk::DARKRED);
+        bg = RGB::named(rltk::RED);
+        glyph = rltk::to_cp437('░');
+    };
+    if !map.visible_tiles[idx] { fg = fg.to_greyscale(); bg = bg.to_greyscale(); }
+
+    (glyph, fg, bg)
+}
+
+fn wall_glyph(map : &Map, x: i32, y: i32) -> rltk::FontCharType {
+    // if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 as i32 { return 219; }
+    if x < 1 {
+        if y < 1 { return 201; }
+        if y > map.height - 2 { return 200; }
+        if is_revealed_and_wall(map, 1, y) { return 204; }
+        return 186;
+    }
+    if x > map.width - 2 {
+        if y < 1 { return 187; }
+        if y > map.height - 2 { return 188; }
+        if is_revealed_and_wall(map, map.width-2, y) { return 185; }
+        return 186;
+    }
+    if y < 1 {
+        if is_revealed_and_wall(map, x, 1) { return 203; }
+        return 205;
+    }
+    if y > map.height - 2 {
+        if is_revealed_and_wall(map, x, map.height-2) { return 202; }
+        return 205;
+    }
+
+    let mut mask : u8 = 0;
+
+    if is_revealed_and_wall(map, x,y-1) { mask += 1; } // N
+    if is_revealed_and_wall(map, x,y+1) { mask += 2; } // S
+    if is_revealed_and_wall(map, x-1,y) { mask += 4; } // W
+    if is_revealed_and_wall(map, x+1,y) { mask += 8; } // E
+
+    match mask {
+        0 => { 10 } // Pillar
+        1 => { 10 } // Wall to north
+        2 => { 10 } // wall to south
+        3 => { 186 } // wall to north and south
+        4 => { 10 } // wall to west
+        5 => { 188 } // wall to north and west
+        6 => { 187 } // wall to south and west
+        7 => { 185 } // wall to north, south and west
+        8 => { 10 } // wall to east
+        9 => { 200 } // wall to north and east
+        10 => { 201 } // wall to south and east
+        11 => { 204 } // wall to north, south and east
+        12 => { 205 } // wall to east and west
+        13 => { 202 } // wall to south, east and west
+        14 => { 203 } // wall to north, west and east
+        15 => { 206 } // wall to all directions
+        _ => { 219 } // missed one?
+    }
+}
+
+fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
+    let idx = map.xy_idx(x,y);
+    map.tiles[idx] == TileType::Wall && map.revealed_tiles[idx]
+}
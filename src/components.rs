@@ -32,12 +32,53 @@ pub struct Viewshed {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Monster {}
 
+/// Townsfolk who wander about and never pick a fight
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Bystander {}
+
+/// Townsfolk who keep to their shop or post
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Vendor {}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct Name { pub name : String }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
+/// Stops line of sight through the tile it stands on
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct BlocksVisibility {}
+
+/// A door blocks the tile and the view through it until it's opened
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Door { pub open : bool }
+
+impl Door {
+    /// Swings `door` open so it no longer blocks movement or sight
+    /// - Returns false if it was already open
+    pub fn open_door(
+        doors : &mut WriteStorage<Door>,
+        blocks_tile : &mut WriteStorage<BlocksTile>,
+        blocks_visibility : &mut WriteStorage<BlocksVisibility>,
+        renderables : &mut WriteStorage<Renderable>,
+        door : Entity,
+    ) -> bool {
+        match doors.get_mut(door) {
+            Some(state) if !state.open => state.open = true,
+            _ => return false,
+        }
+        blocks_tile.remove(door);
+        blocks_visibility.remove(door);
+        if let Some(render) = renderables.get_mut(door) { render.glyph = rltk::to_cp437('/'); }
+        true
+    }
+}
+
+/// Monsters with this can open doors that are in their way
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OpensDoors {}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct CombatStats {
     pub max_hp : i32,
@@ -58,21 +99,33 @@ pub struct ThirstClock {
     pub duration : i32,
 }
 
+/// Damage waiting to be applied, each hit flagged with whether the player dealt it
 #[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct SufferDamage { pub amount : Vec<i32> }
+pub struct SufferDamage { pub amount : Vec<(i32, bool)> }
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32, from_player: bool) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, from_player));
         } else {
-            let dmg = SufferDamage { amount : vec![amount] };
+            let dmg = SufferDamage { amount : vec![(amount, from_player)] };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
 }
 
+/// Character level; the player also banks XP towards the next one
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct Item {}
+pub struct Experience {
+    pub level : i32,
+    pub xp : i32,
+}
+
+/// How hard an item is to come by; the log shows item names in its color
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize, Default)]
+pub enum Rarity { #[default] Common, Uncommon, Rare }
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Item { pub rarity : Rarity }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Consumable {}
@@ -92,13 +145,112 @@ pub struct InflictsDamage { pub damage : i32 }
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct AreaOfEffect { pub radius : i32 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Confusion { pub turns : i32 }
+/// How fast something acts, and the energy it has saved up towards its next turn
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Initiative {
+    pub speed : i32,
+    pub energy : i32,
+}
+
+/// Marks whoever is allowed to act right now
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MyTurn { }
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusKind { Confusion, Poison, Bleeding, Slow, Haste, Regeneration, Blindness }
+
+/// What happens when an effect lands on something that already has it
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Stacking {
+    Refresh,    //? Keep the longer duration and the stronger magnitude
+    Intensify,  //? Add the magnitudes together
+    Extend,     //? Add the durations together
+}
+
+impl StatusKind {
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Poison => Stacking::Intensify,
+            StatusKind::Bleeding => Stacking::Extend,
+            _ => Stacking::Refresh,
+        }
+    }
+
+    /// How the effect reads after "is", e.g. "You are no longer poisoned"
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            StatusKind::Confusion => "confused",
+            StatusKind::Poison => "poisoned",
+            StatusKind::Bleeding => "bleeding",
+            StatusKind::Slow => "slowed",
+            StatusKind::Haste => "hasted",
+            StatusKind::Regeneration => "regenerating",
+            StatusKind::Blindness => "blinded",
+        }
+    }
+}
+
+/// One running effect
+/// - `magnitude` means damage per turn for poison and bleeding, healing per turn
+///   for regeneration, and speed gained or lost for haste and slow
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind : StatusKind,
+    pub turns : i32,
+    pub magnitude : i32,
+}
+
+/// Every effect currently on an entity; each ticks once per turn the entity takes
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatusEffects { pub effects : Vec<StatusEffect> }
+impl StatusEffects {
+    pub fn add_effect(store: &mut WriteStorage<StatusEffects>, target: Entity, effect: StatusEffect) {
+        if store.get(target).is_none() {
+            store.insert(target, StatusEffects::default()).expect("Unable to insert status effects");
+        }
+        let effects = &mut store.get_mut(target).unwrap().effects;
+
+        match effects.iter_mut().find(|e| e.kind == effect.kind) {
+            None => effects.push(effect),
+            Some(existing) => match effect.kind.stacking() {
+                Stacking::Refresh => {
+                    existing.turns = i32::max(existing.turns, effect.turns);
+                    existing.magnitude = i32::max(existing.magnitude, effect.magnitude);
+                }
+                Stacking::Intensify => {
+                    existing.turns = i32::max(existing.turns, effect.turns);
+                    existing.magnitude += effect.magnitude;
+                }
+                Stacking::Extend => {
+                    existing.turns += effect.turns;
+                    existing.magnitude = i32::max(existing.magnitude, effect.magnitude);
+                }
+            },
+        }
+    }
+
+    pub fn has(&self, kind : StatusKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    /// Speed gained from haste less speed lost to slowing
+    pub fn speed_modifier(&self) -> i32 {
+        self.effects.iter().map(|e| match e.kind {
+            StatusKind::Haste => e.magnitude,
+            StatusKind::Slow => -e.magnitude,
+            _ => 0,
+        }).sum()
+    }
+}
+
+/// On an item or trap, the status effect it puts on whatever it hits
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus { pub effect : StatusEffect }
 
 #[derive(Component, Debug, ConvertSaveload)]
 pub struct InBackpack { pub owner : Entity }
 
-#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum EquipmentSlot { Melee, Shield }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -152,7 +304,21 @@ pub struct TriggersOnce { }
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct EntityMoved { }
 
+/// Where an entity stands on a level the player isn't on
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OtherLevelPosition {
+    pub x : i32,
+    pub y : i32,
+    pub depth : i32,
+}
+
 pub struct SerializeMe;
 
 #[derive(Component, Serialize, Deserialize, Clone)]
-pub struct SerializationHelper { pub map : super::map::Map}
+pub struct SerializationHelper {
+    pub map : super::map::Map,
+    pub seed : u64,
+    pub dungeon : super::dungeon::MasterDungeonMap,
+    pub turns : i32,
+    pub log : super::gamelog::GameLog,
+}
@@ -0,0 +1,98 @@
+use rltk::Point;
+use serde::{ Serialize, Deserialize };
+use specs::prelude::*;
+use std::collections::BTreeMap;
+use super::{ Map, TileType, Position, OtherLevelPosition, Viewshed };
+
+/// Every level the player has left, keyed by depth
+/// - Entities on those levels stay in the ECS with an `OtherLevelPosition`
+///   in place of their `Position`, so only the maps are kept here
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct MasterDungeonMap {
+    maps : BTreeMap<i32, Map>,
+}
+
+impl MasterDungeonMap {
+    pub fn new() -> MasterDungeonMap {
+        MasterDungeonMap{ maps : BTreeMap::new() }
+    }
+
+    pub fn store_map(&mut self, map : &Map) {
+        self.maps.insert(map.depth, map.clone());
+    }
+
+    pub fn get_map(&self, depth : i32) -> Option<Map> {
+        let mut map = self.maps.get(&depth)?.clone();
+        //?  The tile index isn't saved, so a map read back from a save has none
+        map.reset_content_index();
+        Some(map)
+    }
+}
+
+/// Stores the current map and takes everything on it except the player off the board
+pub fn freeze_level(ecs : &mut World) {
+    let map = ecs.fetch::<Map>().clone();
+    ecs.write_resource::<MasterDungeonMap>().store_map(&map);
+
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let player_entity = ecs.fetch::<Entity>();
+
+    let mut pos_to_delete : Vec<Entity> = Vec::new();
+    for (entity, pos) in (&entities, &positions).join() {
+        if entity != *player_entity {
+            other_level_positions.insert(entity, OtherLevelPosition{ x: pos.x, y: pos.y, depth: map.depth })
+                .expect("Insert fail");
+            pos_to_delete.push(entity);
+        }
+    }
+
+    for p in pos_to_delete.iter() {
+        positions.remove(*p);
+    }
+}
+
+/// Brings back a stored level and its entities, and puts the player on the
+/// first `arrive_on` tile
+/// - Returns false, changing nothing, if `depth` has never been visited
+pub fn thaw_level(ecs : &mut World, depth : i32, arrive_on : TileType) -> bool {
+    let map = match ecs.fetch::<MasterDungeonMap>().get_map(depth) {
+        None => return false,
+        Some(map) => map,
+    };
+
+    {
+        let entities = ecs.entities();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+
+        let mut pos_to_delete : Vec<Entity> = Vec::new();
+        for (entity, pos) in (&entities, &other_level_positions).join() {
+            if pos.depth == depth {
+                positions.insert(entity, Position{ x: pos.x, y: pos.y }).expect("Insert fail");
+                pos_to_delete.push(entity);
+            }
+        }
+
+        for p in pos_to_delete.iter() {
+            other_level_positions.remove(*p);
+        }
+    }
+
+    let arrival = map.tiles.iter().position(|t| *t == arrive_on).expect("No stairs to arrive on");
+    let (player_x, player_y) = (arrival as i32 % map.width, arrival as i32 / map.width);
+    *ecs.write_resource::<Map>() = map;
+    *ecs.write_resource::<Point>() = Point::new(player_x, player_y);
+
+    let player_entity = *ecs.fetch::<Entity>();
+    if let Some(pos) = ecs.write_storage::<Position>().get_mut(player_entity) {
+        pos.x = player_x;
+        pos.y = player_y;
+    }
+    if let Some(vs) = ecs.write_storage::<Viewshed>().get_mut(player_entity) {
+        vs.dirty = true;
+    }
+
+    true
+}
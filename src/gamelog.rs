@@ -1,3 +1,89 @@
+use serde::{ Serialize, Deserialize };
+use super::Rarity;
+
+//?  Oldest entries are dropped past this, so saves don't grow without bound
+const MAX_ENTRIES : usize = 1000;
+
+/// What a piece of a log line is about, which decides the color it's drawn in
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum LogColor {
+    Normal,
+    Damage,
+    Healing,
+    Status,
+    Notice,
+    Item(Rarity),
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LogSpan {
+    pub color : LogColor,
+    pub text : String,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// The player's turn count when this was logged
+    pub turn : i32,
+    pub spans : Vec<LogSpan>,
+}
+
+impl LogEntry {
+    /// The entry as plain text, without its colors
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct GameLog {
-    pub entries : Vec<String>
+    pub entries : Vec<LogEntry>,
+    /// Stamped on every new entry; kept in step with the player's turn count
+    pub turn : i32,
+}
+
+impl GameLog {
+    pub fn new() -> GameLog { GameLog::default() }
+
+    /// Logs a line of plain text
+    pub fn add<S:ToString>(&mut self, text : S) {
+        self.entry().text(text).push();
+    }
+
+    /// Starts a line built out of colored pieces; nothing is logged until
+    /// `push` is called
+    pub fn entry(&mut self) -> LogBuilder<'_> {
+        LogBuilder{ log : self, spans : Vec::new() }
+    }
+
+    fn push_entry(&mut self, spans : Vec<LogSpan>) {
+        self.entries.push(LogEntry{ turn : self.turn, spans });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(0..excess);
+        }
+    }
+}
+
+pub struct LogBuilder<'a> {
+    log : &'a mut GameLog,
+    spans : Vec<LogSpan>,
+}
+
+impl<'a> LogBuilder<'a> {
+    pub fn colored<S:ToString>(mut self, color : LogColor, text : S) -> Self {
+        self.spans.push(LogSpan{ color, text : text.to_string() });
+        self
+    }
+
+    pub fn text<S:ToString>(self, text : S) -> Self { self.colored(LogColor::Normal, text) }
+    pub fn damage<S:ToString>(self, text : S) -> Self { self.colored(LogColor::Damage, text) }
+    pub fn healing<S:ToString>(self, text : S) -> Self { self.colored(LogColor::Healing, text) }
+    pub fn status<S:ToString>(self, text : S) -> Self { self.colored(LogColor::Status, text) }
+    pub fn notice<S:ToString>(self, text : S) -> Self { self.colored(LogColor::Notice, text) }
+    pub fn item<S:ToString>(self, text : S, rarity : Rarity) -> Self { self.colored(LogColor::Item(rarity), text) }
+
+    pub fn push(self) {
+        self.log.push_entry(self.spans);
+    }
 }
@@ -3,6 +3,8 @@ use specs::prelude::*;
 use super::{
     RunState, Map, CombatStats, Player, GameLog, Name, RexAssets, Hidden,
     Position, State, InBackpack, Viewshed, Equipped, ThirstClock, ThirstState,
+    Experience, systems::damage_system::xp_to_next_level, StatusEffects, StatusKind,
+    LogColor, LogEntry, Rarity, camera::Viewport, mapgen_visualizer::MapgenVisualizer,
 };
 
 pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
@@ -31,20 +33,47 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
         ctx.draw_bar_horizontal(28, 43, 51, stats.hp, stats.max_hp, crm, blk);
 
         match tc.state {
-            ThirstState::Quenched => ctx.print_color(60, 42, blu, blk, "THIRST: Quenched"),
+            ThirstState::Quenched => ctx.print_color(60, 44, blu, blk, "THIRST: Quenched"),
             ThirstState::Normal => { }
-            ThirstState::Thirsty => ctx.print_color(60, 42, prp, blk, "THIRST: Thirsty"),
-            ThirstState::Parched => ctx.print_color(60, 42, mvr, blk, "THIRST: Parched"),
+            ThirstState::Thirsty => ctx.print_color(60, 44, prp, blk, "THIRST: Thirsty"),
+            ThirstState::Parched => ctx.print_color(60, 44, mvr, blk, "THIRST: Parched"),
         }
 
+        //?  The first row inside the box is for the player's state; the log fills the rest
         let log = ecs.fetch::<GameLog>();
-        let mut y = 44;
-        for s in log.entries.iter().rev() {
-            if y < 49 { ctx.print(2, y, s); }
+        let mut y = 45;
+        for entry in log.entries.iter().rev() {
+            if y < 49 { draw_log_entry(ctx, 2, y, 76, entry); }
             y += 1;
         }
     }
 
+    //?  Level and progress towards the next one
+    let experience = ecs.read_storage::<Experience>();
+    for (_player, xp) in (&players, &experience).join() {
+        let level = format!("Level {}", xp.level);
+        ctx.print_color(2, 44, gld, blk, &level);
+        ctx.draw_bar_horizontal(11, 44, 20, xp.xp, xp_to_next_level(xp.level), gld, blk);
+    }
+
+    //?  Active status effects, with the turns each has left
+    let status_effects = ecs.read_storage::<StatusEffects>();
+    for (_player, status) in (&players, &status_effects).join() {
+        let mut x = 33;
+        for effect in status.effects.iter() {
+            let fg = match effect.kind {
+                StatusKind::Haste | StatusKind::Regeneration => RGB::named(rltk::GREEN),
+                StatusKind::Poison => RGB::named(rltk::CHARTREUSE),
+                StatusKind::Bleeding => crm,
+                _ => mvr,
+            };
+            let label = format!("{:?}({})", effect.kind, effect.turns);
+            if x + label.len() as i32 > 59 { break; }
+            ctx.print_color(x, 44, fg, blk, &label);
+            x += label.len() as i32 + 1;
+        }
+    }
+
     //?  Depth Display
     let map = ecs.fetch::<Map>();
     let depth = format!("Depth: {}", map.depth);
@@ -62,11 +91,14 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let hidden = ecs.read_storage::<Hidden>();
 
     let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height { return; }
+    let mouse_world = match Viewport::for_player(ecs).screen_to_world(Point::new(mouse_pos.0, mouse_pos.1)) {
+        Some(world) if world.x < map.width && world.y < map.height => world,
+        _ => return,
+    };
     let mut tooltip : Vec<String> = Vec::new();
     for (name, position, _hidden) in (&names, &positions, !&hidden).join() {
         let idx = map.xy_idx(position.x, position.y);
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 && map.visible_tiles[idx] {
+        if position.x == mouse_world.x && position.y == mouse_world.y && map.visible_tiles[idx] {
             tooltip.push(name.name.to_string());
         }
     }
@@ -108,6 +140,100 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     }
 }
 
+fn log_color(color : LogColor) -> RGB {
+    match color {
+        LogColor::Normal => RGB::named(rltk::WHITE),
+        LogColor::Damage => RGB::named(rltk::RED),
+        LogColor::Healing => RGB::named(rltk::GREEN),
+        LogColor::Status => RGB::named(rltk::MAGENTA),
+        LogColor::Notice => RGB::named(rltk::GOLD),
+        LogColor::Item(Rarity::Common) => RGB::named(rltk::LIGHTGRAY),
+        LogColor::Item(Rarity::Uncommon) => RGB::named(rltk::DEEPSKYBLUE),
+        LogColor::Item(Rarity::Rare) => RGB::named(rltk::ORANGE),
+    }
+}
+
+/// Prints each span of `entry` in its own color, cut off at `width` characters
+fn draw_log_entry(ctx : &mut Rltk, x : i32, y : i32, width : i32, entry : &LogEntry) {
+    let mut x = x;
+    let end = x + width;
+    for span in entry.spans.iter() {
+        let room = (end - x).max(0) as usize;
+        let text : String = span.text.chars().take(room).collect();
+        ctx.print_color(x, y, log_color(span.color), RGB::named(rltk::BLACK), &text);
+        x += text.chars().count() as i32;
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogHistoryResult { Scrolled{ offset : i32 }, Close }
+
+/// The whole game log, newest at the bottom; `offset` is how many entries
+/// the view has been scrolled back from the newest
+pub fn show_log_history(ecs : &World, ctx : &mut Rltk, offset : i32) -> LogHistoryResult {
+    let (pnk, blk, ylw, gry) = (RGB::named(rltk::LIGHTPINK), RGB::named(rltk::BLACK), RGB::named(rltk::KHAKI), RGB::named(rltk::SLATEGREY));
+    let log = ecs.fetch::<GameLog>();
+    let rows = 44;
+    let max_offset = (log.entries.len() as i32 - rows).max(0);
+    let offset = offset.clamp(0, max_offset);
+
+    ctx.cls();
+    ctx.draw_box(0, 0, 79, 49, pnk, blk);
+    ctx.print_color(3, 0, ylw, blk, " Log history ");
+    ctx.print_color(3, 49, ylw, blk, " Up/Down, PgUp/PgDn, Home/End to scroll - ESC to close ");
+
+    let newest = log.entries.len() as i32 - offset;
+    let oldest = (newest - rows).max(0);
+    for (row, entry) in log.entries[oldest as usize..newest as usize].iter().enumerate() {
+        let y = 2 + row as i32;
+        ctx.print_color(2, y, gry, blk, format!("{:>5}", entry.turn));
+        draw_log_entry(ctx, 8, y, 70, entry);
+    }
+
+    let offset = match ctx.key {
+        None => offset,
+        Some(key) => match key {
+            VirtualKeyCode::Escape => return LogHistoryResult::Close,
+            VirtualKeyCode::Up => offset + 1,
+            VirtualKeyCode::Down => offset - 1,
+            VirtualKeyCode::PageUp => offset + rows,
+            VirtualKeyCode::PageDown => offset - rows,
+            VirtualKeyCode::Home => max_offset,
+            VirtualKeyCode::End => 0,
+            _ => offset,
+        }
+    };
+    LogHistoryResult::Scrolled{ offset : offset.clamp(0, max_offset) }
+}
+
+/// Lists every action with the keys currently bound to it
+pub fn show_help(ecs : &World, ctx : &mut Rltk) -> ItemMenuResult {
+    use super::keybindings::{ Keybindings, Action };
+    let (pnk, blk, ylw, gry) = (RGB::named(rltk::LIGHTPINK), RGB::named(rltk::BLACK), RGB::named(rltk::KHAKI), RGB::named(rltk::SLATEGREY));
+    let bindings = ecs.fetch::<Keybindings>();
+
+    let count = Action::ALL.len() as i32;
+    let top = 25 - count / 2;
+    ctx.draw_box(10, top-2, 59, count+3, pnk, blk);
+    ctx.print_color(13, top-2, ylw, blk, "Keys");
+    ctx.print_color(13, top+count+1, ylw, blk, "Any key to close");
+
+    for (y, action) in (top..).zip(Action::ALL.iter()) {
+        ctx.print_color(12, y, pnk, blk, action.description());
+        let keys = bindings.keys_for(*action);
+        if keys.is_empty() {
+            ctx.print_color(32, y, gry, blk, "unbound");
+        } else {
+            ctx.print_color(32, y, ylw, blk, keys.join(", "));
+        }
+    }
+
+    match ctx.key {
+        None => ItemMenuResult::NoResponse,
+        Some(_) => ItemMenuResult::Cancel,
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ItemMenuResult { Cancel, NoResponse, Selected }
 
@@ -157,6 +283,77 @@ pub fn show_inventory(gs: &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Optio
     }
 }
 
+/// Lists every save slot; empty slots can only be picked when `saving`
+pub fn save_slot_menu(gs : &mut State, ctx : &mut Rltk, saving : bool) -> (ItemMenuResult, Option<usize>) {
+    use super::saveload_system::{ SAVE_SLOTS, SlotStatus, slot_status, slot_name, format_timestamp };
+
+    let (pnk, blk, ylw, gry, red) = (
+        RGB::named(rltk::LIGHTPINK),
+        RGB::named(rltk::BLACK),
+        RGB::named(rltk::KHAKI),
+        RGB::named(rltk::SLATEGREY),
+        RGB::named(rltk::RED),
+    );
+
+    let count = SAVE_SLOTS;
+    let mut y = (25 - (count / 2)) as i32;
+    ctx.draw_box(5, y-2, 69, (count+3) as i32, pnk, blk);
+    ctx.print_color(8, y-2, ylw, blk, if saving { "Save to which slot?" } else { "Load which slot?" });
+    ctx.print_color(8, y+count as i32+1, ylw, blk, "ESC to cancel");
+
+    let mut selectable : Vec<bool> = Vec::new();
+    for slot in 0..count {
+        ctx.set(7, y, pnk, blk, rltk::to_cp437('('));
+        ctx.set(8, y, ylw, blk, 97+slot as rltk::FontCharType);
+        ctx.set(9, y, pnk, blk, rltk::to_cp437(')'));
+        ctx.print(11, y, slot_name(slot));
+
+        match slot_status(&gs.save_dir, slot) {
+            SlotStatus::Empty => {
+                ctx.print_color(19, y, gry, blk, "empty");
+                selectable.push(saving);
+            }
+            SlotStatus::Saved(meta) => {
+                let line = format!("{}, level {} - depth {}, {} turns - {}",
+                    meta.character, meta.level, meta.depth, meta.turns, format_timestamp(meta.saved_at));
+                ctx.print(19, y, line);
+                selectable.push(true);
+            }
+            SlotStatus::Unreadable(reason) => {
+                let line : String = format!("unreadable: {}", reason).chars().take(54).collect();
+                ctx.print_color(19, y, red, blk, line);
+                selectable.push(true);
+            }
+        }
+        y += 1;
+    }
+
+    if let Some(error) = &gs.menu_error {
+        let width = 66;
+        let error : String = error.chars().take(width).collect();
+        ctx.print_color(7, y+2, red, blk, error);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => {
+            match key {
+                VirtualKeyCode::Escape => { (ItemMenuResult::Cancel, None) }
+                _ => {
+                    let selection = rltk::letter_to_option(key);
+                    if selection > -1 && selection < count as i32 {
+                        if selectable[selection as usize] {
+                            return (ItemMenuResult::Selected, Some(selection as usize));
+                        }
+                        gs.menu_error = Some(format!("{} has nothing to load", slot_name(selection as usize)));
+                    }
+                    (ItemMenuResult::NoResponse, None)
+                }
+            }
+        }
+    }
+}
+
 pub fn drop_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
@@ -259,6 +456,7 @@ pub fn ranged_target(gs: &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuR
     );
 
     ctx.print_color(5,0, ylw, blk, "Select Target:");
+    let view = Viewport::for_player(&gs.ecs);
 
     let mut available_cells = Vec::new();
     let visible = viewsheds.get(*player_entity);
@@ -266,7 +464,8 @@ pub fn ranged_target(gs: &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuR
         for idx in visible.visible_tiles.iter() {
             let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, blu);
+                let screen = view.world_to_screen(*idx);
+                ctx.set_bg(screen.x, screen.y, blu);
                 available_cells.push(idx);
             }
         }
@@ -275,12 +474,12 @@ pub fn ranged_target(gs: &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuR
     }
 
     let mouse_pos = ctx.mouse_pos();
-    let mut valid_target = false;
-    for idx in available_cells.iter() { if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 { valid_target = true; } }
+    let mouse_world = view.screen_to_world(Point::new(mouse_pos.0, mouse_pos.1));
+    let valid_target = mouse_world.is_some_and(|target| available_cells.iter().any(|idx| **idx == target));
     if valid_target {
         ctx.set_bg(mouse_pos.0,mouse_pos.1, cyn);
         if ctx.left_click {
-            return (ItemMenuResult::Selected, Some(Point::new(mouse_pos.0,mouse_pos.1)));
+            return (ItemMenuResult::Selected, mouse_world);
         }
     } else {
         ctx.set_bg(mouse_pos.0,mouse_pos.1, red);
@@ -292,10 +491,12 @@ pub fn ranged_target(gs: &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuR
     (ItemMenuResult::NoResponse, None)
 }
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub enum MainMenuSelection {
     NewGame,
+    Seed,
     LoadGame,
+    MapgenVisualizer,
     Quit
 }
 #[derive(PartialEq, Copy, Clone)]
@@ -305,7 +506,7 @@ pub enum MainMenuResult {
 }
 
 pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
-    let save_exists = super::saveload_system::does_save_exist();
+    let save_exists = super::saveload_system::any_saves(&gs.save_dir);
     let runstate = gs.ecs.fetch::<RunState>();
     let (
         ylw, blk, mga, pnk, whe, gry
@@ -321,14 +522,21 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
     ctx.render_xp_sprite(&assets.menu, 0,0);
 
 
-    ctx.draw_box_double(24, 18, 31, 14, whe, blk);
-    ctx.print_color_centered(21, ylw, blk, "Rouge Rust Rogue");
+    ctx.draw_box_double(24, 18, 31, 16, whe, blk);
+    ctx.print_color_centered(20, ylw, blk, "Rouge Rust Rogue");
 
     if let RunState::MainMenu{ menu_selection : selection } = *runstate {
         if selection == NewGame {
-            ctx.print_color_centered(25, mga, blk, "Start Hunting");
+            ctx.print_color_centered(23, mga, blk, "Start Hunting");
+        } else {
+            ctx.print_color_centered(23, pnk, blk, "Start Hunting");
+        }
+
+        let seed = format!("Seed: {}", gs.next_seed);
+        if selection == Seed {
+            ctx.print_color_centered(25, mga, blk, &seed);
         } else {
-            ctx.print_color_centered(25, pnk, blk, "Start Hunting");
+            ctx.print_color_centered(25, pnk, blk, &seed);
         }
 
         if save_exists {
@@ -339,38 +547,79 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
             }
         }
 
+        if selection == MapgenVisualizer {
+            ctx.print_color_centered(29, mga, blk, "Map Generator");
+        } else {
+            ctx.print_color_centered(29, pnk, blk, "Map Generator");
+        }
+
         if selection == Quit {
-            ctx.print_color_centered(29, mga, blk, "Quit");
+            ctx.print_color_centered(31, mga, blk, "Quit");
         } else {
-            ctx.print_color_centered(29, pnk, blk, "Quit");
+            ctx.print_color_centered(31, pnk, blk, "Quit");
         }
 
-        ctx.print_color_centered(31, gry, blk, format!("Use Up/Down Arrows and Enter"));
+        if selection == Seed {
+            ctx.print_color_centered(33, gry, blk, "Type a seed, Enter for random");
+        } else {
+            ctx.print_color_centered(33, gry, blk, "Use Up/Down Arrows and Enter");
+        }
 
         use MainMenuSelection::*;
         match ctx.key {
             None => return MainMenuResult::NoSelection{ selected: selection },
             Some(key) => {
+                if selection == Seed {
+                    //?  The first key typed replaces the pre-filled seed rather than extending it
+                    let typing = seed_digit(key).is_some() || key == VirtualKeyCode::Back;
+                    if typing && !gs.seed_typed {
+                        gs.next_seed = 0;
+                        gs.seed_typed = true;
+                    }
+                    if let Some(digit) = seed_digit(key) {
+                        if gs.next_seed < u64::MAX / 100 { gs.next_seed = gs.next_seed * 10 + digit; }
+                        return MainMenuResult::NoSelection{ selected: selection }
+                    }
+                    match key {
+                        VirtualKeyCode::Back => {
+                            gs.next_seed /= 10;
+                            return MainMenuResult::NoSelection{ selected: selection }
+                        }
+                        VirtualKeyCode::Return => {
+                            gs.next_seed = super::rng::random_seed();
+                            gs.seed_typed = false;
+                            return MainMenuResult::NoSelection{ selected: selection }
+                        }
+                        _ => {}
+                    }
+                }
+
                 match key {
                     VirtualKeyCode::Escape => { return MainMenuResult::NoSelection{ selected: Quit } }
                     VirtualKeyCode::Down => {
                         let mut newselection;
                         match selection {
-                            NewGame => newselection = LoadGame,
-                            LoadGame => newselection = Quit,
+                            NewGame => newselection = Seed,
+                            Seed => newselection = LoadGame,
+                            LoadGame => newselection = MapgenVisualizer,
+                            MapgenVisualizer => newselection = Quit,
                             Quit => newselection = NewGame
                         }
-                        if newselection == LoadGame && !save_exists { newselection = Quit; }
+                        if newselection == LoadGame && !save_exists { newselection = MapgenVisualizer; }
+                        gs.seed_typed = false;
                         return MainMenuResult::NoSelection{ selected: newselection }
                     }
                     VirtualKeyCode::Up => {
                         let mut newselection;
                         match selection {
                             NewGame => newselection = Quit,
-                            LoadGame => newselection = NewGame,
-                            Quit => newselection = LoadGame
+                            Seed => newselection = NewGame,
+                            LoadGame => newselection = Seed,
+                            MapgenVisualizer => newselection = LoadGame,
+                            Quit => newselection = MapgenVisualizer
                         }
-                        if newselection == LoadGame && !save_exists { newselection = NewGame; }
+                        if newselection == LoadGame && !save_exists { newselection = Seed; }
+                        gs.seed_typed = false;
                         return MainMenuResult::NoSelection{ selected: newselection }
                     }
                     VirtualKeyCode::Return => return MainMenuResult::Selected{ selected: selection },
@@ -383,6 +632,76 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
     MainMenuResult::NoSelection { selected: MainMenuSelection::NewGame }
 }
 
+/// The map generator debug screen: plays back the chosen builder's snapshots
+/// with the settings and controls underneath
+/// - `show_on_new_game` is the setting for playing back a new game's first
+///   level as it's built, toggled from here with P
+pub fn mapgen_visualizer(vis : &mut MapgenVisualizer, show_on_new_game : &mut bool, ctx : &mut Rltk) -> ItemMenuResult {
+    let (ylw, blk, pnk, gry) = (RGB::named(rltk::KHAKI), RGB::named(rltk::BLACK), RGB::named(rltk::LIGHTPINK), RGB::named(rltk::SLATEGREY));
+    vis.update(ctx.frame_time_ms);
+    super::camera::render_debug_map(vis.snapshot(), ctx);
+
+    let map = vis.snapshot();
+    ctx.draw_box(0, 43, 79, 6, pnk, blk);
+    ctx.print_color(2, 44, ylw, blk, format!("{} ({}/{})", vis.builder_name(), vis.chain + 1, MapgenVisualizer::chain_count()));
+    ctx.print_color(2, 45, pnk, blk, format!("Depth {}  Seed {}  {}x{}", vis.depth, vis.seed, map.width, map.height));
+    let playback = if vis.paused { "Paused" } else { "Playing" };
+    ctx.print_color(2, 46, pnk, blk, format!("Snapshot {}/{}  Floor {:.1}%  {}", vis.index + 1, vis.history.len(), vis.floor_percent(), playback));
+    let on_new_game = if *show_on_new_game { "on" } else { "off" };
+    ctx.print_color(52, 46, pnk, blk, format!("New game playback: {}", on_new_game));
+    ctx.print_color(2, 47, gry, blk, "Up/Down builder  PgUp/PgDn depth  Left/Right step  Home/End  Space pause");
+    ctx.print_color(2, 48, gry, blk, "Type a seed, Enter rebuild, R random seed, P new game playback, Escape leave");
+
+    let Some(key) = ctx.key else { return ItemMenuResult::NoResponse };
+    //?  The first key typed after a rebuild starts a new seed rather than extending the old one
+    if (seed_digit(key).is_some() || key == VirtualKeyCode::Back) && !vis.seed_typed {
+        vis.seed = 0;
+        vis.seed_typed = true;
+    }
+    if let Some(digit) = seed_digit(key) {
+        if vis.seed < u64::MAX / 100 { vis.seed = vis.seed * 10 + digit; }
+        return ItemMenuResult::NoResponse;
+    }
+    match key {
+        VirtualKeyCode::Escape => return ItemMenuResult::Cancel,
+        VirtualKeyCode::Up => vis.select_chain(-1),
+        VirtualKeyCode::Down => vis.select_chain(1),
+        VirtualKeyCode::PageUp => vis.select_depth(-1),
+        VirtualKeyCode::PageDown => vis.select_depth(1),
+        VirtualKeyCode::Left => vis.step(-1),
+        VirtualKeyCode::Right => vis.step(1),
+        VirtualKeyCode::Home => vis.step(-(vis.history.len() as i32)),
+        VirtualKeyCode::End => vis.step(vis.history.len() as i32),
+        VirtualKeyCode::Space => vis.paused = !vis.paused,
+        VirtualKeyCode::P => *show_on_new_game = !*show_on_new_game,
+        VirtualKeyCode::Back => vis.seed /= 10,
+        VirtualKeyCode::Return => vis.regenerate(),
+        VirtualKeyCode::R => {
+            vis.seed = super::rng::random_seed();
+            vis.regenerate();
+        }
+        _ => {}
+    }
+    ItemMenuResult::NoResponse
+}
+
+fn seed_digit(key : VirtualKeyCode) -> Option<u64> {
+    use VirtualKeyCode::*;
+    match key {
+        Key0 | Numpad0 => Some(0),
+        Key1 | Numpad1 => Some(1),
+        Key2 | Numpad2 => Some(2),
+        Key3 | Numpad3 => Some(3),
+        Key4 | Numpad4 => Some(4),
+        Key5 | Numpad5 => Some(5),
+        Key6 | Numpad6 => Some(6),
+        Key7 | Numpad7 => Some(7),
+        Key8 | Numpad8 => Some(8),
+        Key9 | Numpad9 => Some(9),
+        _ => None,
+    }
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum GameOverResult { NoSelection, QuitToMenu }
 
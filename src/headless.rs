@@ -0,0 +1,103 @@
+use specs::prelude::*;
+use super::{
+    Map, RunState, PlayerCommand, init_world, run_systems, goto_next_level, goto_previous_level,
+    generate_world_map,
+    raws, systems::damage_system::delete_the_dead, systems::particle_system::age_particles,
+};
+
+/// Runs the game without an rltk window
+/// - Plays the same turn sequence as `State::tick`, minus anything that only
+///   exists to be drawn (the map reveal sweeps the whole map at once and
+///   particles expire straight away)
+/// - `ecs` is left public so callers can inspect or rig the world between steps
+pub struct Simulation {
+    pub ecs : World,
+}
+
+impl Simulation {
+    /// Loads the raws and builds depth 1 of the run for `seed`
+    pub fn new(seed : u64) -> Result<Simulation, String> {
+        raws::load_raws()?;
+
+        let mut sim = Simulation{ ecs : World::new() };
+        init_world(&mut sim.ecs, seed);
+        generate_world_map(&mut sim.ecs, 1, false);
+        sim.settle();
+
+        Ok(sim)
+    }
+
+    pub fn player(&self) -> Entity { *self.ecs.fetch::<Entity>() }
+
+    pub fn run_state(&self) -> RunState { *self.ecs.fetch::<RunState>() }
+
+    pub fn depth(&self) -> i32 { self.ecs.fetch::<Map>().depth }
+
+    /// Plays one player command and everything the world does in response
+    /// - Returns the state the game is left waiting in: `AwaitingInput`, or
+    ///   `GameOver` once the player has died
+    pub fn step(&mut self, command : PlayerCommand) -> RunState {
+        if self.run_state() != RunState::AwaitingInput { return self.run_state(); }
+
+        let newrunstate = command.apply(&mut self.ecs);
+        self.set_run_state(newrunstate);
+        self.settle();
+
+        self.run_state()
+    }
+
+    /// Plays `commands` in order, stopping early if the player dies
+    pub fn run(&mut self, commands : &[PlayerCommand]) -> RunState {
+        for command in commands.iter() {
+            if self.step(*command) == RunState::GameOver { break; }
+        }
+
+        self.run_state()
+    }
+
+    /// Advances through the non-interactive states until the game wants input
+    fn settle(&mut self) {
+        loop {
+            let newrunstate = match self.run_state() {
+                RunState::PreRun => {
+                    run_systems(&mut self.ecs);
+                    RunState::Ticking
+                }
+                RunState::PlayerTurn => {
+                    run_systems(&mut self.ecs);
+                    match self.run_state() {
+                        RunState::MapReveal{ .. } => RunState::MapReveal{ row: 0 },
+                        _ => RunState::Ticking,
+                    }
+                }
+                RunState::Ticking => {
+                    run_systems(&mut self.ecs);
+                    self.run_state()
+                }
+                RunState::MapReveal{ .. } => {
+                    let mut map = self.ecs.fetch_mut::<Map>();
+                    for revealed in map.revealed_tiles.iter_mut() { *revealed = true; }
+                    RunState::Ticking
+                }
+                RunState::NextLevel => {
+                    goto_next_level(&mut self.ecs);
+                    RunState::PreRun
+                }
+                RunState::PreviousLevel => {
+                    goto_previous_level(&mut self.ecs);
+                    RunState::PreRun
+                }
+                _ => return,
+            };
+
+            self.set_run_state(newrunstate);
+            delete_the_dead(&mut self.ecs);
+            age_particles(&mut self.ecs, f32::MAX);
+        }
+    }
+
+    fn set_run_state(&mut self, newrunstate : RunState) {
+        let mut runwriter = self.ecs.write_resource::<RunState>();
+        *runwriter = newrunstate;
+    }
+}
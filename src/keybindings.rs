@@ -0,0 +1,179 @@
+use rltk::VirtualKeyCode;
+use serde::{ Deserialize };
+use std::collections::BTreeMap;
+
+/// Where the game looks for the player's key layout. It's optional; without
+/// it the classic layout is used.
+pub const KEYBINDINGS_PATH : &str = "./keybindings.json";
+
+/// Everything a key can be bound to
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug, Deserialize)]
+pub enum Action {
+    MoveNorth, MoveSouth, MoveWest, MoveEast,
+    MoveNorthWest, MoveNorthEast, MoveSouthWest, MoveSouthEast,
+    Wait, PickUp, Descend, Ascend,
+    Inventory, Drop, Remove,
+    Log, Help, SaveAndQuit, ExportMap,
+}
+
+impl Action {
+    /// Every action, in the order the help screen lists them
+    pub const ALL : [Action; 19] = [
+        Action::MoveNorth, Action::MoveSouth, Action::MoveWest, Action::MoveEast,
+        Action::MoveNorthWest, Action::MoveNorthEast, Action::MoveSouthWest, Action::MoveSouthEast,
+        Action::Wait, Action::PickUp, Action::Descend, Action::Ascend,
+        Action::Inventory, Action::Drop, Action::Remove,
+        Action::Log, Action::Help, Action::SaveAndQuit, Action::ExportMap,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::MoveNorth => "Move north",
+            Action::MoveSouth => "Move south",
+            Action::MoveWest => "Move west",
+            Action::MoveEast => "Move east",
+            Action::MoveNorthWest => "Move north-west",
+            Action::MoveNorthEast => "Move north-east",
+            Action::MoveSouthWest => "Move south-west",
+            Action::MoveSouthEast => "Move south-east",
+            Action::Wait => "Wait a turn",
+            Action::PickUp => "Pick up",
+            Action::Descend => "Go down stairs",
+            Action::Ascend => "Go up stairs",
+            Action::Inventory => "Use an item",
+            Action::Drop => "Drop an item",
+            Action::Remove => "Remove equipment",
+            Action::Log => "Log history",
+            Action::Help => "This help",
+            Action::SaveAndQuit => "Save and quit",
+            Action::ExportMap => "Export the map",
+        }
+    }
+}
+
+/// The built-in layouts a config file can start from
+#[derive(PartialEq, Copy, Clone, Debug, Deserialize, Default)]
+pub enum Preset {
+    /// A/F/D/S and W/E/X/C, plus the arrow keys
+    #[default]
+    Classic,
+    /// h/j/k/l and y/u/b/n
+    Vi,
+    /// The number pad, with 5 to wait
+    Numpad,
+    /// The arrow keys, with Home/PgUp/End/PgDn for diagonals
+    Arrows,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct KeybindingsFile {
+    #[serde(default)]
+    preset : Preset,
+    /// Key names to actions, applied over the preset; `null` unbinds a key
+    #[serde(default)]
+    bindings : BTreeMap<String, Option<Action>>,
+}
+
+#[derive(Debug)]
+pub struct Keybindings {
+    keys : BTreeMap<VirtualKeyCode, Action>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Keybindings { Keybindings::preset(Preset::Classic) }
+}
+
+impl Keybindings {
+    pub fn preset(preset : Preset) -> Keybindings {
+        use VirtualKeyCode::*;
+        use Action::*;
+        let mut keys = BTreeMap::new();
+        let movement : &[(VirtualKeyCode, Action)] = match preset {
+            Preset::Classic => &[
+                (D, MoveNorth), (S, MoveSouth), (A, MoveWest), (F, MoveEast),
+                (W, MoveNorthWest), (E, MoveNorthEast), (X, MoveSouthWest), (C, MoveSouthEast),
+                (Up, MoveNorth), (Down, MoveSouth), (Left, MoveWest), (Right, MoveEast),
+                (T, Drop), (R, Remove), (L, Log),
+            ],
+            Preset::Vi => &[
+                (K, MoveNorth), (J, MoveSouth), (H, MoveWest), (L, MoveEast),
+                (Y, MoveNorthWest), (U, MoveNorthEast), (B, MoveSouthWest), (N, MoveSouthEast),
+                (D, Drop), (R, Remove), (M, Log),
+            ],
+            Preset::Numpad => &[
+                (Numpad8, MoveNorth), (Numpad2, MoveSouth), (Numpad4, MoveWest), (Numpad6, MoveEast),
+                (Numpad7, MoveNorthWest), (Numpad9, MoveNorthEast), (Numpad1, MoveSouthWest), (Numpad3, MoveSouthEast),
+                (Numpad5, Wait), (D, Drop), (R, Remove), (M, Log),
+            ],
+            Preset::Arrows => &[
+                (Up, MoveNorth), (Down, MoveSouth), (Left, MoveWest), (Right, MoveEast),
+                (Home, MoveNorthWest), (PageUp, MoveNorthEast), (End, MoveSouthWest), (PageDown, MoveSouthEast),
+                (D, Drop), (R, Remove), (M, Log),
+            ],
+        };
+        let shared = [
+            (Space, Wait), (G, PickUp), (Period, Descend), (Comma, Ascend),
+            (I, Inventory), (Slash, Help), (F1, Help), (Escape, SaveAndQuit), (F2, ExportMap),
+        ];
+        for (key, action) in shared.iter().chain(movement.iter()) {
+            keys.insert(*key, *action);
+        }
+        Keybindings{ keys }
+    }
+
+    pub fn action_for(&self, key : VirtualKeyCode) -> Option<Action> {
+        self.keys.get(&key).copied()
+    }
+
+    /// Every key bound to `action`, by name
+    pub fn keys_for(&self, action : Action) -> Vec<String> {
+        self.keys.iter().filter(|(_, a)| **a == action).map(|(key, _)| key_name(*key)).collect()
+    }
+
+    /// Builds the bindings described by a config file's contents
+    pub fn parse(config : &str) -> Result<Keybindings, String> {
+        let file : KeybindingsFile = serde_json::from_str(config).map_err(|e| e.to_string())?;
+        let mut bindings = Keybindings::preset(file.preset);
+        for (name, action) in file.bindings {
+            let key = key_from_name(&name).ok_or_else(|| format!("\"{}\" isn't a key name", name))?;
+            match action {
+                Some(action) => { bindings.keys.insert(key, action); }
+                None => { bindings.keys.remove(&key); }
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+/// Reads `KEYBINDINGS_PATH`, falling back to the classic layout if there
+/// isn't one
+/// - Errors name the file and, for JSON problems, the line and column
+pub fn load_keybindings() -> Result<Keybindings, String> {
+    match std::fs::read_to_string(KEYBINDINGS_PATH) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Keybindings::default()),
+        Err(e) => Err(format!("Unable to read {}: {}", KEYBINDINGS_PATH, e)),
+        Ok(config) => Keybindings::parse(&config).map_err(|e| format!("Invalid {}: {}", KEYBINDINGS_PATH, e)),
+    }
+}
+
+//?  Every key that can be named in a config file
+const NAMED_KEYS : [VirtualKeyCode; 69] = {
+    use VirtualKeyCode::*;
+    [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+        Up, Down, Left, Right, Home, End, PageUp, PageDown,
+        Space, Return, Escape, Tab, Back,
+        Period, Comma, Slash, Semicolon, Minus, Equals,
+        F1, F2, F3, F4,
+    ]
+};
+
+/// The name a key goes by in config files and on the help screen
+pub fn key_name(key : VirtualKeyCode) -> String { format!("{:?}", key) }
+
+pub fn key_from_name(name : &str) -> Option<VirtualKeyCode> {
+    NAMED_KEYS.iter().find(|key| key_name(**key).eq_ignore_ascii_case(name)).copied()
+}
@@ -0,0 +1,616 @@
+extern crate serde;
+
+use rltk::{ GameState, Rltk, Point };
+use specs::prelude::*;
+use specs::saveload::{ SimpleMarker, SimpleMarkerAllocator };
+
+mod components; // import components
+pub use components::*; // make its public contents available
+mod map;
+pub use map::*;
+mod player;
+pub use player::*;
+mod rect;
+pub use rect::Rect;
+mod gui;
+pub use gui::*;
+mod rex_assets;
+pub use rex_assets::*;
+mod gamelog;
+pub use gamelog::*;
+pub mod spawner;
+pub mod random_table;
+pub mod map_builders;
+pub mod raws;
+pub mod rng;
+pub mod dungeon;
+pub mod camera;
+pub mod keybindings;
+pub mod headless;
+pub mod mapgen_visualizer;
+pub mod map_export;
+pub mod mapgen_stats;
+// - References the `systems.rs` file which give us access to the files within
+//   the `/systems` directory.
+mod systems;
+use systems::map_indexing_system::MapIndexingSystem;
+use systems::monster_ai_system::MonsterAI;
+use systems::bystander_ai_system::BystanderAI;
+use systems::visibility_system::VisibilitySystem;
+use systems::initiative_system::InitiativeSystem;
+use systems::status_effect_system::StatusEffectSystem;
+pub use systems::saveload_system;
+pub use systems::trigger_system;
+pub use systems::particle_system::*;
+pub use systems::damage_system::DamageSystem;
+pub use systems::thirst_system::ThirstSystem;
+pub use systems::melee_combat_system::MeleeCombatSystem;
+pub use systems::inventory_system::{
+    ItemCollectionSystem,
+    ItemUseSystem,
+    ItemDropSystem,
+    ItemRemoveSystem,
+};
+
+pub struct State {
+    pub ecs: World,
+    mapgen_next_state : Option<RunState>,
+    mapgen_history : Vec<Map>,
+    mapgen_index : usize,
+    mapgen_timer : f32,
+    /// Whether a new game plays back how its first level was built before
+    /// it starts; set from the map generator screen
+    pub show_mapgen : bool,
+    /// The map generator debug screen, while it's open
+    visualizer : Option<mapgen_visualizer::MapgenVisualizer>,
+    pub next_seed : u64,
+    /// Whether the player has started typing over the pre-filled `next_seed`
+    pub seed_typed : bool,
+    pub save_dir : std::path::PathBuf,
+    /// Shown in the save/load menus when the last attempt failed
+    pub menu_error : Option<String>,
+}
+
+/// Runs every gameplay system once, in order
+pub fn run_systems(ecs : &mut World) {
+    let mut vis = VisibilitySystem{};
+    vis.run_now(ecs);
+    let mut initiative = InitiativeSystem{};
+    initiative.run_now(ecs);
+    let mut status_effects = StatusEffectSystem{};
+    status_effects.run_now(ecs);
+    let mut mob = MonsterAI{};
+    mob.run_now(ecs);
+    let mut bystanders = BystanderAI{};
+    bystanders.run_now(ecs);
+    let mut mapindex = MapIndexingSystem{};
+    mapindex.run_now(ecs);
+    let mut melee = MeleeCombatSystem{};
+    melee.run_now(ecs);
+    let mut triggers = trigger_system::TriggerSystem{};
+    triggers.run_now(ecs);
+    let mut damage = DamageSystem{};
+    damage.run_now(ecs);
+    let mut pickup = ItemCollectionSystem{};
+    pickup.run_now(ecs);
+    let mut use_items = ItemUseSystem{};
+    use_items.run_now(ecs);
+    let mut drop_items = ItemDropSystem{};
+    drop_items.run_now(ecs);
+    let mut remove_item = ItemRemoveSystem{};
+    remove_item.run_now(ecs);
+    let mut thirst_system = ThirstSystem{};
+    thirst_system.run_now(ecs);
+    let mut particles = systems::particle_system::ParticleSpawnSystem{};
+    particles.run_now(ecs);
+
+    ecs.maintain();
+
+    //?  Hand control over once the pass that gave the player a turn is done
+    let player_ready = ecs.read_storage::<MyTurn>().contains(*ecs.fetch::<Entity>());
+    let mut runstate = ecs.write_resource::<RunState>();
+    if *runstate == RunState::Ticking && player_ready {
+        *runstate = RunState::AwaitingInput;
+    }
+}
+
+impl State {
+    fn run_systems(&mut self) {
+        run_systems(&mut self.ecs);
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    PlayerTurn,
+    Ticking,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting {range: i32, item: Entity},
+    MainMenu { menu_selection : gui::MainMenuSelection },
+    SaveGame,
+    LoadGame,
+    NextLevel,
+    PreviousLevel,
+    ShowRemoveItem,
+    GameOver,
+    MapReveal { row : i32 },
+    ShowLog { offset : i32 },
+    ShowHelp,
+    MapGeneration,
+    MapgenVisualizer,
+}
+
+impl GameState for State {//  GameState is a trait implemented on State
+    fn tick(&mut self, ctx: &mut Rltk) {
+        let mut newrunstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            newrunstate = *runstate;
+        }
+        ctx.cls();
+        systems::particle_system::cull_dead_particles(&mut self.ecs, ctx);
+
+        match newrunstate {
+            RunState::MainMenu{ .. } => {}
+            RunState::GameOver{ .. } => {}
+            RunState::MapgenVisualizer => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                draw_ui(&self.ecs, ctx);
+            }
+        }
+
+
+        match newrunstate {
+            RunState::MapGeneration => {
+                if self.mapgen_index >= self.mapgen_history.len() {
+                    newrunstate = self.mapgen_next_state.unwrap();
+                } else {
+                    ctx.cls();
+                    camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
+
+                    self.mapgen_timer += ctx.frame_time_ms;
+                    if self.mapgen_timer > 300.0 {
+                        self.mapgen_timer = 0.0;
+                        self.mapgen_index += 1;
+                    }
+                }
+            }
+            RunState::PreRun => {
+                self.run_systems();
+                self.ecs.maintain();
+                newrunstate = RunState::Ticking;
+            }
+            RunState::MainMenu{ .. } => {
+                let result = gui::main_menu(self, ctx);
+                match result {
+                    MainMenuResult::NoSelection{ selected } => newrunstate = RunState::MainMenu{ menu_selection: selected },
+                    MainMenuResult::Selected{ selected } => {
+                        match selected {
+                            MainMenuSelection::NewGame => {
+                                //?  Same seed, same level: only rebuild it when there's a build to play back
+                                let same_seed = self.next_seed == self.ecs.fetch::<rng::RunSeed>().seed;
+                                if same_seed && !self.show_mapgen {
+                                    newrunstate = RunState::PreRun;
+                                } else {
+                                    self.reset_world(self.next_seed);
+                                    self.mapgen_next_state = Some(RunState::PreRun);
+                                    newrunstate = RunState::MapGeneration;
+                                }
+                            }
+                            MainMenuSelection::Seed => newrunstate = RunState::MainMenu{ menu_selection: selected },
+                            MainMenuSelection::LoadGame => {
+                                self.menu_error = None;
+                                newrunstate = RunState::LoadGame;
+                            },
+                            MainMenuSelection::MapgenVisualizer => {
+                                self.visualizer = Some(mapgen_visualizer::MapgenVisualizer::new(self.next_seed));
+                                newrunstate = RunState::MapgenVisualizer;
+                            }
+                            MainMenuSelection::Quit => { ::std::process::exit(0); }
+                        }
+                    }
+                }
+            }
+            RunState::AwaitingInput => {
+                newrunstate = player_input(self, ctx);
+            }
+            RunState::PlayerTurn => {
+                self.run_systems();
+                self.ecs.maintain();
+                match *self.ecs.fetch::<RunState>() {
+                    RunState::MapReveal{ .. } => newrunstate = RunState::MapReveal{ row: 0 },
+                    _ => newrunstate = RunState::Ticking,
+                }
+            }
+            RunState::Ticking => {
+                //?  Keep handing out turns until the player gets one or dies
+                while newrunstate == RunState::Ticking {
+                    self.run_systems();
+                    self.ecs.maintain();
+                    systems::damage_system::delete_the_dead(&mut self.ecs);
+                    newrunstate = *self.ecs.fetch::<RunState>();
+                }
+            }
+            RunState::ShowInventory => {
+                let result = show_inventory(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => {},
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let is_item_ranged = self.ecs.read_storage::<Ranged>().get(item_entity).map(|r| r.range);
+                        if let Some(range) = is_item_ranged {
+                            newrunstate = RunState::ShowTargeting{ range, item: item_entity };
+                        } else {
+                            newrunstate = PlayerCommand::UseItem{ item: item_entity, target: None }.apply(&mut self.ecs);
+                        }
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let result = drop_item_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        newrunstate = PlayerCommand::DropItem{ item: item_entity }.apply(&mut self.ecs);
+                    }
+                }
+            }
+            RunState::ShowRemoveItem => {
+                let result = gui::remove_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => { }
+                    gui::ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        newrunstate = PlayerCommand::RemoveItem{ item: item_entity }.apply(&mut self.ecs);
+                    }
+                }
+            }
+            RunState::ShowTargeting{range, item} => {
+                let result = gui::ranged_target(self, ctx, range);
+                match result.0 {
+                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        newrunstate = PlayerCommand::UseItem{ item, target: result.1 }.apply(&mut self.ecs);
+                    }
+                }
+            }
+            RunState::MapReveal{row} => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x, row);
+                    map.revealed_tiles[idx] = true;
+                }
+                if row >= map.height-1 {
+                    newrunstate = RunState::Ticking;
+                } else {
+                    newrunstate = RunState::MapReveal{ row: row+1 };
+                }
+            }
+            RunState::SaveGame => {
+                let result = gui::save_slot_menu(self, ctx, true);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => {
+                        self.menu_error = None;
+                        newrunstate = RunState::AwaitingInput;
+                    }
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let slot = result.1.unwrap();
+                        match saveload_system::save_game(&mut self.ecs, &self.save_dir, slot) {
+                            Ok(()) => {
+                                self.menu_error = None;
+                                newrunstate = RunState::MainMenu{ menu_selection : MainMenuSelection::Quit };
+                            }
+                            Err(e) => self.menu_error = Some(e),
+                        }
+                    }
+                }
+            }
+            RunState::LoadGame => {
+                let result = gui::save_slot_menu(self, ctx, false);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => {
+                        self.menu_error = None;
+                        newrunstate = RunState::MainMenu{ menu_selection : MainMenuSelection::LoadGame };
+                    }
+                    gui::ItemMenuResult::NoResponse => {}
+                    gui::ItemMenuResult::Selected => {
+                        let slot = result.1.unwrap();
+                        match saveload_system::load_game(&mut self.ecs, &self.save_dir, slot) {
+                            Ok(()) => {
+                                self.menu_error = None;
+                                saveload_system::delete_save(&self.save_dir, slot);
+                                newrunstate = RunState::AwaitingInput;
+                            }
+                            Err(e) => {
+                                //?  A save that broke halfway through loading leaves nothing to return to
+                                let player_entity = *self.ecs.fetch::<Entity>();
+                                if !self.ecs.entities().is_alive(player_entity) {
+                                    self.reset_world(self.next_seed);
+                                }
+                                self.menu_error = Some(e);
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::ShowLog{ offset } => {
+                match gui::show_log_history(&self.ecs, ctx, offset) {
+                    gui::LogHistoryResult::Close => newrunstate = RunState::AwaitingInput,
+                    gui::LogHistoryResult::Scrolled{ offset } => newrunstate = RunState::ShowLog{ offset },
+                }
+            }
+            RunState::MapgenVisualizer => {
+                ctx.cls();
+                let visualizer = self.visualizer.as_mut().expect("The map generator screen needs a visualizer");
+                if gui::mapgen_visualizer(visualizer, &mut self.show_mapgen, ctx) == gui::ItemMenuResult::Cancel {
+                    self.visualizer = None;
+                    newrunstate = RunState::MainMenu{ menu_selection : MainMenuSelection::MapgenVisualizer };
+                }
+            }
+            RunState::ShowHelp => {
+                if gui::show_help(&self.ecs, ctx) == gui::ItemMenuResult::Cancel {
+                    newrunstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::NextLevel => {
+                self.goto_next_level();
+                newrunstate = RunState::PreRun;
+            }
+            RunState::PreviousLevel => {
+                self.goto_previous_level();
+                newrunstate = RunState::PreRun;
+            }
+            RunState::GameOver => {
+                let result = gui::game_over(ctx);
+                match result {
+                    gui::GameOverResult::NoSelection => { }
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game_over_cleanup();
+                        newrunstate = RunState::MainMenu{ menu_selection: gui:: MainMenuSelection::NewGame };
+                    }
+                }
+            }
+        }
+
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = newrunstate;
+        }
+
+        systems::damage_system::delete_the_dead(&mut self.ecs);
+    }
+}
+
+impl State {
+    /// Builds a fresh world for `seed`, ready to show the mapgen visualizer
+    /// and then the main menu
+    pub fn new(seed : u64) -> State {
+        let mut gs = State {
+            ecs: World::new(),
+            mapgen_index : 0,
+            mapgen_history : Vec::new(),
+            mapgen_next_state : Some(RunState::MainMenu{ menu_selection: MainMenuSelection::NewGame }),
+            mapgen_timer : 0.0,
+            show_mapgen : false,
+            visualizer : None,
+            next_seed : seed,
+            seed_typed : false,
+            save_dir : std::path::PathBuf::from(saveload_system::DEFAULT_SAVE_DIR),
+            menu_error : None,
+        };
+        init_world(&mut gs.ecs, seed);
+        gs.ecs.insert(RunState::MapGeneration{});
+        // gs.ecs.insert(RunState::MainMenu{ menu_selection: MainMenuSelection::NewGame });
+        gs.ecs.fetch_mut::<GameLog>().add("Gathering mana...");
+        gs.generate_world_map(1);
+
+        gs
+    }
+
+    fn generate_world_map(&mut self, new_depth : i32) {
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.mapgen_history = generate_world_map(&mut self.ecs, new_depth, self.show_mapgen);
+    }
+
+    fn goto_next_level(&mut self) {
+        goto_next_level(&mut self.ecs);
+    }
+
+    fn goto_previous_level(&mut self) {
+        goto_previous_level(&mut self.ecs);
+    }
+
+    fn game_over_cleanup(&mut self) {
+        self.next_seed = rng::random_seed();
+        self.reset_world(self.next_seed);
+    }
+
+    fn reset_world(&mut self, seed : u64) {
+        reset_world(&mut self.ecs, seed);
+        self.generate_world_map(1);
+    }
+}
+
+/// Registers every component and inserts the resources a game needs, then
+/// spawns the player. The map itself is left empty until
+/// `generate_world_map` is called.
+pub fn init_world(ecs : &mut World, seed : u64) {
+    ecs.register::<Position>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<Viewshed>();
+    ecs.register::<Monster>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<CombatStats>();
+    ecs.register::<DoesMelee>();
+    ecs.register::<SufferDamage>();
+    ecs.register::<Item>();
+    ecs.register::<Consumable>();
+    ecs.register::<Ranged>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<ProvidesWater>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<StatusEffects>();
+    ecs.register::<InflictsStatus>();
+    ecs.register::<InBackpack>();
+    ecs.register::<WantsToPickupItem>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<WantsToRemoveItem>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleePowerBonus>();
+    ecs.register::<DefenseBonus>();
+    ecs.register::<ThirstClock>();
+    ecs.register::<MagicMapper>();
+    ecs.register::<Hidden>();
+    ecs.register::<EntryTrigger>();
+    ecs.register::<TriggersOnce>();
+    ecs.register::<EntityMoved>();
+    ecs.register::<OtherLevelPosition>();
+    ecs.register::<Initiative>();
+    ecs.register::<MyTurn>();
+    ecs.register::<Experience>();
+    ecs.register::<BlocksVisibility>();
+    ecs.register::<Door>();
+    ecs.register::<OpensDoors>();
+    ecs.register::<Bystander>();
+    ecs.register::<Vendor>();
+    ecs.register::<ParticleLifetime>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+
+    ecs.insert(Map::new(1, camera::VIEW_WIDTH, camera::VIEW_HEIGHT));
+    ecs.insert(Point::new(0, 0));
+    ecs.insert(rng::RunSeed{ seed });
+    ecs.insert(rng::gameplay_rng(seed, 1));
+    ecs.insert(dungeon::MasterDungeonMap::new());
+    ecs.insert(TurnCounter{ turns : 0 });
+    let player_entity = spawner::player(ecs, 0, 0);
+    ecs.insert(player_entity);
+    ecs.insert(RunState::PreRun);
+    ecs.insert(GameLog::new());
+    ecs.insert(keybindings::Keybindings::default());
+    ecs.insert(systems::particle_system::ParticleBuilder::new());
+}
+
+/// Builds and populates the map for `new_depth` and moves the player onto it
+/// - Returns the builder's snapshots for the mapgen visualizer, which are
+///   only taken if `record_history` is set
+pub fn generate_world_map(ecs : &mut World, new_depth : i32, record_history : bool) -> Vec<Map> {
+    let seed = ecs.fetch::<rng::RunSeed>().seed;
+    let mut rng = rng::map_rng(seed, new_depth);
+    let (width, height) = map_builders::level_size(new_depth);
+    let mut builder = map_builders::level_builder(new_depth, &mut rng, width, height, record_history);
+    builder.build_map(&mut rng);
+    let mapgen_history = builder.build_data.history.clone();
+    let player_start;
+    {
+        let mut worldmap_resource = ecs.write_resource::<Map>();
+        *worldmap_resource = builder.build_data.map.clone();
+        player_start = builder.build_data.starting_position.as_mut().unwrap().clone();
+        //?  Every level but the first has a way back up where the player arrives
+        if new_depth > 1 {
+            let start_idx = worldmap_resource.xy_idx(player_start.x, player_start.y);
+            worldmap_resource.tiles[start_idx] = TileType::UpStairs;
+        }
+    }
+
+    builder.spawn_entities(ecs);
+
+    let (player_x, player_y) = (player_start.x, player_start.y);
+    let mut player_position = ecs.write_resource::<Point>();
+    *player_position = Point::new(player_x, player_y);
+    let mut position_components = ecs.write_storage::<Position>();
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos_comp = position_components.get_mut(*player_entity);
+    if let Some(player_pos_comp) = player_pos_comp {
+        player_pos_comp.x = player_x;
+        player_pos_comp.y = player_y;
+    }
+
+    let mut viewshed_components = ecs.write_storage::<Viewshed>();
+    let vs = viewshed_components.get_mut(*player_entity);
+    if let Some(vs) = vs {
+        vs.dirty = true;
+    }
+
+    mapgen_history
+}
+
+/// Leaves the current level for the one below, building it if the player
+/// hasn't been down there before
+pub fn goto_next_level(ecs : &mut World) {
+    //?  Put the current level away, entities and all
+    dungeon::freeze_level(ecs);
+
+    let current_depth = ecs.fetch::<Map>().depth;
+    let first_visit = !dungeon::thaw_level(ecs, current_depth + 1, TileType::UpStairs);
+    if first_visit {
+        generate_world_map(ecs, current_depth + 1, false);
+    }
+
+    let player_entity = ecs.fetch::<Entity>();
+    let mut gamelog = ecs.fetch_mut::<gamelog::GameLog>();
+    gamelog.add("You descend to the next level. Your heart beats with anticipation.");
+    //?  Only a fresh level patches the player up, so the stairs can't be used to heal
+    if first_visit {
+        let mut player_health_store = ecs.write_storage::<CombatStats>();
+        let player_health = player_health_store.get_mut(*player_entity);
+        if let Some(player_health) = player_health {
+            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+        }
+    }
+}
+
+/// Climbs back to the level above, which the player has always already visited
+pub fn goto_previous_level(ecs : &mut World) {
+    dungeon::freeze_level(ecs);
+
+    let current_depth = ecs.fetch::<Map>().depth;
+    if !dungeon::thaw_level(ecs, current_depth - 1, TileType::DownStairs) {
+        panic!("There is no stored level above depth {}", current_depth);
+    }
+
+    let mut gamelog = ecs.fetch_mut::<gamelog::GameLog>();
+    gamelog.add("You ascend to the previous level.");
+}
+
+/// Throws away the current run and starts a fresh one from `seed`
+/// - The caller still has to `generate_world_map` for depth 1
+pub fn reset_world(ecs : &mut World, seed : u64) {
+    let mut to_delete = Vec::new();
+    for e in ecs.entities().join() {
+        to_delete.push(e);
+    }
+    for del in to_delete.iter() {
+        ecs.delete_entity(*del).expect("Failed to delete on cleanup");
+    }
+
+    {
+        let player_entity = spawner::player(ecs, 0 , 0);
+        let mut player_entity_writer = ecs.write_resource::<Entity>();
+        *player_entity_writer = player_entity;
+    }
+
+    ecs.insert(rng::RunSeed{ seed });
+    ecs.insert(rng::gameplay_rng(seed, 1));
+    ecs.insert(dungeon::MasterDungeonMap::new());
+    ecs.insert(TurnCounter{ turns : 0 });
+    ecs.insert(GameLog::new());
+}
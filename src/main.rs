@@ -1,424 +1,30 @@
-extern crate serde;
+use hellorust::*;
 
-use rltk::{ GameState, Rltk, Point };
-use specs::prelude::*;
-use specs::saveload::{ SimpleMarker, SimpleMarkerAllocator };
-
-mod components; // import components
-pub use components::*; // make its public contents available
-mod map;
-pub use map::*;
-mod player;
-pub use player::*;
-mod rect;
-pub use rect::Rect;
-mod gui;
-pub use gui::*;
-mod rex_assets;
-pub use rex_assets::*;
-mod gamelog;
-pub use gamelog::*;
-pub mod spawner;
-pub mod random_table;
-pub mod map_builders;
-// - References the `systems.rs` file which give us access to the files within
-//   the `/systems` directory.
-mod systems;
-use systems::map_indexing_system::MapIndexingSystem;
-use systems::monster_ai_system::MonsterAI;
-use systems::visibility_system::VisibilitySystem;
-pub use systems::saveload_system;
-pub use systems::trigger_system;
-pub use systems::particle_system::*;
-pub use systems::damage_system::DamageSystem;
-pub use systems::thirst_system::ThirstSystem;
-pub use systems::melee_combat_system::MeleeCombatSystem;
-pub use systems::inventory_system::{
-    ItemCollectionSystem,
-    ItemUseSystem,
-    ItemDropSystem,
-    ItemRemoveSystem,
-};
-
-const SHOW_MAPGEN_VISUALIZER : bool = true;
-
-pub struct State {
-    pub ecs: World,
-    mapgen_next_state : Option<RunState>,
-    mapgen_history : Vec<Map>,
-    mapgen_index : usize,
-    mapgen_timer : f32,
-}
-
-impl State {
-    fn run_systems(&mut self) {
-        let mut vis = VisibilitySystem{};
-        vis.run_now(&self.ecs);
-        let mut mob = MonsterAI{};
-        mob.run_now(&self.ecs);
-        let mut mapindex = MapIndexingSystem{};
-        mapindex.run_now(&self.ecs);
-        let mut melee = MeleeCombatSystem{};
-        melee.run_now(&self.ecs);
-        let mut triggers = trigger_system::TriggerSystem{};
-        triggers.run_now(&self.ecs);
-        let mut damage = DamageSystem{};
-        damage.run_now(&self.ecs);
-        let mut pickup = ItemCollectionSystem{};
-        pickup.run_now(&self.ecs);
-        let mut use_items = ItemUseSystem{};
-        use_items.run_now(&self.ecs);
-        let mut drop_items = ItemDropSystem{};
-        drop_items.run_now(&self.ecs);
-        let mut remove_item = ItemRemoveSystem{};
-        remove_item.run_now(&self.ecs);
-        let mut thirst_system = ThirstSystem{};
-        thirst_system.run_now(&self.ecs);
-        let mut particles = systems::particle_system::ParticleSpawnSystem{};
-        particles.run_now(&self.ecs);
-
-        self.ecs.maintain();
-    }
-}
-
-#[derive(PartialEq, Copy, Clone)]
-pub enum RunState {
-    AwaitingInput,
-    PreRun,
-    PlayerTurn,
-    MonsterTurn,
-    ShowInventory,
-    ShowDropItem,
-    ShowTargeting {range: i32, item: Entity},
-    MainMenu { menu_selection : gui::MainMenuSelection },
-    SaveGame,
-    NextLevel,
-    ShowRemoveItem,
-    GameOver,
-    MapReveal { row : i32 },
-    MapGeneration,
-}
-
-impl GameState for State {//  GameState is a trait implemented on State
-    fn tick(&mut self, ctx: &mut Rltk) {
-        let mut newrunstate;
-        {
-            let runstate = self.ecs.fetch::<RunState>();
-            newrunstate = *runstate;
-        }
-        ctx.cls();
-        systems::particle_system::cull_dead_particles(&mut self.ecs, ctx);
-
-        match newrunstate {
-            // RunState::MapGeneration => {
-            //     if !SHOW_MAPGEN_VISUALIZER { newrunstate = self.mapgen_next_state.unwrap(); }
-
-            //     ctx.cls();
-            //     draw_map(&self.mapgen_history[self.mapgen_index], ctx);
-
-            //     self.mapgen_timer += ctx.frame_time_ms;
-            //     if self.mapgen_timer > 300.0 {
-            //         self.mapgen_timer = 0.0;
-            //         self.mapgen_index += 1;
-            //         if self.mapgen_index >= self.mapgen_history.len() {
-            //             newrunstate = self.mapgen_next_state.unwrap();
-            //         }
-            //     }
-            // }
-            RunState::MainMenu{ .. } => {}
-            RunState::GameOver{ .. } => {}
-            _ => {
-                draw_map(&self.ecs.fetch::<Map>(), ctx);
-
-                {
-                    let positions = self.ecs.read_storage::<Position>();
-                    let renderables = self.ecs.read_storage::<Renderable>();
-                    let hidden = self.ecs.read_storage::<Hidden>();
-                    let map = self.ecs.fetch::<Map>();
-
-                    let mut data = (&positions, &renderables, !&hidden).join().collect::<Vec<_>>();
-                    data.sort_by( |&a, &b| b.1.render_order.cmp(&a.1.render_order) );
-                    for (pos, render, _hidden) in data.iter() {
-                        let idx = map.xy_idx(pos.x, pos.y);
-                        if map.visible_tiles[idx] { ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph) }
-                    }
-
-                    draw_ui(&self.ecs, ctx);
-                }
-            }
-        }
-
-
-        match newrunstate {
-            RunState::MapGeneration => {
-                if !SHOW_MAPGEN_VISUALIZER { newrunstate = self.mapgen_next_state.unwrap(); }
-
-                ctx.cls();
-                draw_map(&self.mapgen_history[self.mapgen_index], ctx);
-
-                self.mapgen_timer += ctx.frame_time_ms;
-                if self.mapgen_timer > 300.0 {
-                    self.mapgen_timer = 0.0;
-                    self.mapgen_index += 1;
-                    if self.mapgen_index >= self.mapgen_history.len() {
-                        newrunstate = self.mapgen_next_state.unwrap();
-                    }
-                }
-            }
-            RunState::PreRun => {
-                self.run_systems();
-                self.ecs.maintain();
-                newrunstate = RunState::AwaitingInput;
-            }
-            RunState::MainMenu{ .. } => {
-                let result = gui::main_menu(self, ctx);
-                match result {
-                    MainMenuResult::NoSelection{ selected } => newrunstate = RunState::MainMenu{ menu_selection: selected },
-                    MainMenuResult::Selected{ selected } => {
-                        match selected {
-                            MainMenuSelection::NewGame => newrunstate = RunState::PreRun,
-                            MainMenuSelection::LoadGame => {
-                                saveload_system::load_game(&mut self.ecs);
-                                newrunstate = RunState::AwaitingInput;
-                                saveload_system::delete_save();
-                            },
-                            MainMenuSelection::Quit => { ::std::process::exit(0); }
-                        }
-                    }
-                }
-            }
-            RunState::AwaitingInput => {
-                newrunstate = player_input(self, ctx);
-            }
-            RunState::PlayerTurn => {
-                self.run_systems();
-                self.ecs.maintain();
-                match *self.ecs.fetch::<RunState>() {
-                    RunState::MapReveal{ .. } => newrunstate = RunState::MapReveal{ row: 0 },
-                    _ => newrunstate = RunState::MonsterTurn,
-                }
-            }
-            RunState::MonsterTurn => {
-                self.run_systems();
-                self.ecs.maintain();
-                newrunstate = RunState::AwaitingInput;
-            }
-            RunState::ShowInventory => {
-                let result = show_inventory(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {},
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let is_ranged = self.ecs.read_storage::<Ranged>();
-                        let is_item_ranged = is_ranged.get(item_entity);
-                        if let Some(is_item_ranged) = is_item_ranged {
-                            newrunstate = RunState::ShowTargeting{ range: is_item_ranged.range, item: item_entity };
-                        } else {
-                            let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                            intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item: item_entity, target: None })
-                                .expect("Unable to intentionalize");
-                            newrunstate = RunState::PlayerTurn;
-                        }
-                    }
-                }
-            }
-            RunState::ShowDropItem => {
-                let result = drop_item_menu(self, ctx);
-                match result.0 {
-                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    ItemMenuResult::NoResponse => {}
-                    ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
-                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToDropItem{ item: item_entity })
-                            .expect("Unable to insert intent");
-                        newrunstate = RunState::PlayerTurn;
-                    }
-                }
-            }
-            RunState::ShowRemoveItem => {
-                let result = gui::remove_item_menu(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => { }
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
-                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToRemoveItem{ item: item_entity })
-                            .expect("Unable to intentionalize removing item");
-                        newrunstate = RunState::PlayerTurn;
-                    }
-                }
-            }
-            RunState::ShowTargeting{range, item} => {
-                let result = gui::ranged_target(self, ctx, range);
-                match result.0 {
-                    ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    ItemMenuResult::NoResponse => {}
-                    ItemMenuResult::Selected => {
-                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
-                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item, target: result.1 })
-                            .expect("Unable to intentionalize");
-                        newrunstate = RunState::PlayerTurn;
-                    }
-                }
-            }
-            RunState::MapReveal{row} => {
-                let mut map = self.ecs.fetch_mut::<Map>();
-                for x in 0..MAPWIDTH {
-                    let idx = map.xy_idx(x as i32, row);
-                    map.revealed_tiles[idx] = true;
-                }
-                if row as usize == MAPHEIGHT-1 {
-                    newrunstate = RunState::MonsterTurn;
-                } else {
-                    newrunstate = RunState::MapReveal{ row: row+1 };
-                }
-            }
-            RunState::SaveGame => {
-                saveload_system::save_game(&mut self.ecs);
-                newrunstate = RunState::MainMenu{ menu_selection : MainMenuSelection::Quit }
-            }
-            RunState::NextLevel => {
-                self.goto_next_level();
-                newrunstate = RunState::PreRun;
-            }
-            RunState::GameOver => {
-                let result = gui::game_over(ctx);
-                match result {
-                    gui::GameOverResult::NoSelection => { }
-                    gui::GameOverResult::QuitToMenu => {
-                        self.game_over_cleanup();
-                        newrunstate = RunState::MainMenu{ menu_selection: gui:: MainMenuSelection::NewGame };
-                    }
-                }
-            }
-        }
-
-        {
-            let mut runwriter = self.ecs.write_resource::<RunState>();
-            *runwriter = newrunstate;
-        }
-
-        systems::damage_system::delete_the_dead(&mut self.ecs);
-    }
-}
-
-impl State {
-    fn generate_world_map(&mut self, new_depth : i32) {
-        self.mapgen_index = 0;
-        self.mapgen_timer = 0.0;
-        self.mapgen_history.clear();
-        let mut builder = map_builders::random_builder(new_depth);
-        builder.build_map();
-        self.mapgen_history = builder.get_snapshot_history();
-        let player_start;
-        {
-            let mut worldmap_resource = self.ecs.write_resource::<Map>();
-            *worldmap_resource = builder.get_map();
-            player_start = builder.get_starting_position();
-        }
-
-        builder.spawn_entities(&mut self.ecs);
-
-        let (player_x, player_y) = (player_start.x, player_start.y);
-        let mut player_position = self.ecs.write_resource::<Point>();
-        *player_position = Point::new(player_x, player_y);
-        let mut position_components = self.ecs.write_storage::<Position>();
-        let player_entity = self.ecs.fetch::<Entity>();
-        let player_pos_comp = position_components.get_mut(*player_entity);
-        if let Some(player_pos_comp) = player_pos_comp {
-            player_pos_comp.x = player_x;
-            player_pos_comp.y = player_y;
-        }
-
-        let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
-        let vs = viewshed_components.get_mut(*player_entity);
-        if let Some(vs) = vs {
-            vs.dirty = true;
-        }
-    }
-
-    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
-        let entities = self.ecs.entities();
-        let player = self.ecs.read_storage::<Player>();
-        let backpack = self.ecs.read_storage::<InBackpack>();
-        let player_entity = self.ecs.fetch::<Entity>();
-        let equipped = self.ecs.read_storage::<Equipped>();
-
-        let mut to_delete : Vec<Entity> = Vec::new();
-        for entity in entities.join() {
-            let mut should_delete = true;
-
-            let p = player.get(entity);
-            if let Some(_p) = p { should_delete = false; }
-
-            let bp = backpack.get(entity);
-            if let Some(bp) = bp {
-                if bp.owner == *player_entity { should_delete = false; }
-            }
-
-            let eq = equipped.get(entity);
-            if let Some(eq) = eq {
-                if eq.owner == *player_entity {
-                    should_delete = false;
-                }
-            }
-
-            if should_delete { to_delete.push(entity); }
-        }
-
-        to_delete
-    }
-
-    fn goto_next_level(&mut self) {
-        //?  Remove non-player entities
-        let to_delete = self.entities_to_remove_on_level_change();
-        for target in to_delete {
-            self.ecs.delete_entity(target).expect("Unable to delete entity");
-        }
-
-        //?  Build a new map and associate the player with it
-        let current_depth;
-        {
-            let worldmap_resource = self.ecs.fetch::<Map>();
-            current_depth = worldmap_resource.depth;
-        }
-        self.generate_world_map(current_depth + 1);
-
-        //?  Place Player
-        let player_entity = self.ecs.fetch::<Entity>();
-        let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
-        gamelog.entries.push("You descend to the next level. Your heart beats with anticipation.".to_string());
-        let mut player_health_store = self.ecs.write_storage::<CombatStats>();
-        let player_health = player_health_store.get_mut(*player_entity);
-        if let Some(player_health) = player_health {
-            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+fn main() -> rltk::BError {
+    use rltk::RltkBuilder;
+    //?  Subcommands run without a window and exit when they're done
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let finished = match args.first().map(String::as_str) {
+        Some("export-map") => Some(map_export::run_export_command(&args[1..])),
+        Some("mapgen-stats") => Some(mapgen_stats::run_stats_command(&args[1..])),
+        _ => None,
+    };
+    if let Some(result) = finished {
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            ::std::process::exit(1);
         }
+        return Ok(());
     }
-
-    fn game_over_cleanup(&mut self) {
-        let mut to_delete = Vec::new();
-        for e in self.ecs.entities().join() {
-            to_delete.push(e);
-        }
-        for del in to_delete.iter() {
-            self.ecs.delete_entity(*del).expect("Failed to delete on cleanup");
-        }
-
-        {
-            let player_entity = spawner::player(&mut self.ecs, 0 , 0);
-            let mut player_entity_writer = self.ecs.write_resource::<Entity>();
-            *player_entity_writer = player_entity;
-        }
-
-        self.generate_world_map(1);
+    if let Err(e) = raws::load_raws() {
+        eprintln!("{}", e);
+        ::std::process::exit(1);
     }
-}
+    let keys = keybindings::load_keybindings().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        ::std::process::exit(1);
+    });
 
-fn main() -> rltk::BError {
-    use rltk::RltkBuilder;
     let context = RltkBuilder::simple80x50()
         .with_title("Rust Rouge Rogue")
         .build()?;
@@ -426,66 +32,12 @@ fn main() -> rltk::BError {
     //     .with_title("Rust Rouge Rogue")
     //     .build()?;
     // context.with_post_scanlines(true);
+    let seed = rng::seed_from_args().unwrap_or_else(rng::random_seed);
     // Create a game-state
-    let mut gs = State {
-        ecs: World::new(),
-        mapgen_index : 0,
-        mapgen_history : Vec::new(),
-        mapgen_next_state : Some(RunState::MainMenu{ menu_selection: MainMenuSelection::NewGame }),
-        mapgen_timer : 0.0,
-    };
-    // Register components to the game-state
-    gs.ecs.register::<Position>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<Viewshed>();
-    gs.ecs.register::<Monster>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<CombatStats>();
-    gs.ecs.register::<DoesMelee>();
-    gs.ecs.register::<SufferDamage>();
-    gs.ecs.register::<Item>();
-    gs.ecs.register::<Consumable>();
-    gs.ecs.register::<Ranged>();
-    gs.ecs.register::<ProvidesHealing>();
-    gs.ecs.register::<ProvidesWater>();
-    gs.ecs.register::<InflictsDamage>();
-    gs.ecs.register::<AreaOfEffect>();
-    gs.ecs.register::<Confusion>();
-    gs.ecs.register::<InBackpack>();
-    gs.ecs.register::<WantsToPickupItem>();
-    gs.ecs.register::<WantsToUseItem>();
-    gs.ecs.register::<WantsToDropItem>();
-    gs.ecs.register::<WantsToRemoveItem>();
-    gs.ecs.register::<Equippable>();
-    gs.ecs.register::<Equipped>();
-    gs.ecs.register::<MeleePowerBonus>();
-    gs.ecs.register::<DefenseBonus>();
-    gs.ecs.register::<ThirstClock>();
-    gs.ecs.register::<MagicMapper>();
-    gs.ecs.register::<Hidden>();
-    gs.ecs.register::<EntryTrigger>();
-    gs.ecs.register::<TriggersOnce>();
-    gs.ecs.register::<EntityMoved>();
-    gs.ecs.register::<ParticleLifetime>();
-    gs.ecs.register::<SimpleMarker<SerializeMe>>();
-    gs.ecs.register::<SerializationHelper>();
-
-    gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
-
-    gs.ecs.insert(Map::new(1));
-    gs.ecs.insert(Point::new(0, 0));
-    gs.ecs.insert(rltk::RandomNumberGenerator::new());
-    let player_entity = spawner::player(&mut gs.ecs, 0, 0);
-    gs.ecs.insert(player_entity);
-    gs.ecs.insert(RunState::MapGeneration{});
-    // gs.ecs.insert(RunState::MainMenu{ menu_selection: MainMenuSelection::NewGame });
-    gs.ecs.insert(GameLog{ entries : vec!["Gathering mana...".to_string()] });
-    gs.ecs.insert(systems::particle_system::ParticleBuilder::new());
-    gs.ecs.insert(rex_assets::RexAssets::new());
-
-    gs.generate_world_map(1);
+    let mut gs = State::new(seed);
+    if let Some(dir) = saveload_system::save_dir_from_args() { gs.save_dir = dir; }
+    gs.ecs.insert(RexAssets::new());
+    gs.ecs.insert(keys);
 
     rltk::main_loop(context, gs) //  Calls into the `rltk` namespace to activate `main_loop
 }
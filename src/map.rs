@@ -1,18 +1,36 @@
-use rltk::{ Rltk, RGB, BaseMap, Algorithm2D, Point };
+use rltk::{ BaseMap, Algorithm2D, Point };
 use serde::{ Serialize, Deserialize };
 use std::collections::HashSet;
 use specs::prelude::*;
 use super::{ Rect };
 
-pub const MAPHEIGHT : usize = 43;
-pub const MAPWIDTH : usize = 80;
-pub const MAPCOUNT : usize = MAPHEIGHT * MAPWIDTH;
 
-#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    UpStairs,
+    //?  Outdoor and indoor ground, for the town
+    Road,
+    Grass,
+    Gravel,
+    WoodFloor,
+    Bridge,
+    ShallowWater,
+    DeepWater,
+}
+
+impl TileType {
+    /// Whether anything can stand on this kind of tile
+    pub fn walkable(self) -> bool {
+        !matches!(self, TileType::Wall | TileType::DeepWater)
+    }
+
+    /// Whether this kind of tile blocks line of sight
+    pub fn opaque(self) -> bool {
+        self == TileType::Wall
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
@@ -28,7 +46,12 @@ pub struct Map {
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
-    pub tile_content : Vec<Vec<Entity>>
+    pub tile_content : Vec<Vec<Entity>>,
+
+    /// Tiles something see-through-proof stands on, such as a closed door
+    #[serde(skip_serializing)]
+    #[serde(skip_deserializing)]
+    pub view_blocked : HashSet<usize>,
 }
 impl Map {
     pub fn xy_idx(&self, x: i32, y: i32) -> usize {
@@ -44,15 +67,18 @@ impl Map {
         }
     }
 
-    pub fn new(new_depth : i32) -> Map {
+    /// A solid block of wall `width` by `height` tiles, for builders to carve into
+    pub fn new(new_depth : i32, width : i32, height : i32) -> Map {
+        let map_tile_count = (width * height) as usize;
         Map{
-            tiles : vec![TileType::Wall; MAPCOUNT],
-            width : MAPWIDTH as i32,
-            height : MAPHEIGHT as i32,
-            revealed_tiles : vec![false; MAPCOUNT],
-            visible_tiles : vec![false; MAPCOUNT],
-            blocked : vec![false; MAPCOUNT],
-            tile_content : vec![Vec::new(); MAPCOUNT],
+            tiles : vec![TileType::Wall; map_tile_count],
+            width,
+            height,
+            revealed_tiles : vec![false; map_tile_count],
+            visible_tiles : vec![false; map_tile_count],
+            blocked : vec![false; map_tile_count],
+            tile_content : vec![Vec::new(); map_tile_count],
+            view_blocked : HashSet::new(),
             depth : new_depth,
             bloodstains : HashSet::new(),
         }
@@ -66,10 +92,15 @@ impl Map {
 
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
-            self.blocked[i] = *tile == TileType::Wall;
+            self.blocked[i] = !tile.walkable();
         }
     }
 
+    /// Rebuilds the (unsaved) per-tile entity index at the right size
+    pub fn reset_content_index(&mut self) {
+        self.tile_content = vec![Vec::new(); (self.width * self.height) as usize];
+    }
+
     pub fn clear_content_index(&mut self) {
         for content in self.tile_content.iter_mut() {
             content.clear();
@@ -85,7 +116,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, idx:usize) -> bool {
-        self.tiles[idx as usize] == TileType::Wall
+        self.tiles[idx as usize].opaque() || self.view_blocked.contains(&idx)
     }
 
     fn get_pathing_distance(&self, idx1:usize, idx2:usize) -> f32 {
@@ -116,100 +147,3 @@ impl BaseMap for Map {
         exits
     }
 }
-
-pub fn draw_map(map : &Map, ctx: &mut Rltk) {
-    let mut x = 0;
-    let mut y = 0;
-    for (idx, tile) in map.tiles.iter().enumerate() {
-
-        if map.revealed_tiles[idx] {
-            let mut glyph;
-            let mut fg;
-            let mut bg = RGB::named(rltk::BLACK);
-
-            match tile {
-                TileType::Floor => {
-                    glyph = rltk::to_cp437('·');
-                    fg = RGB::from_f32(0.0, 0.5, 0.5);
-                }
-                TileType::Wall => {
-                    glyph = wall_glyph(&*map, x,y);
-                    fg = RGB::named(rltk::VIOLET);
-                },
-                TileType::DownStairs => {
-                    glyph = rltk::to_cp437('»');
-                    fg = RGB::from_f32(0., 1.0, 1.0);
-                }
-            }
-            if map.bloodstains.contains(&idx) {
-                fg = RGB::named(rltk::DARKRED);
-                bg = RGB::named(rltk::RED);
-                glyph = rltk::to_cp437('░');
-            };
-            if !map.visible_tiles[idx] { fg = fg.to_greyscale(); bg = bg.to_greyscale(); }
-            ctx.set(x,y, fg, bg, glyph);
-        }
-
-        x += 1;
-        if x > MAPWIDTH as i32 - 1 {
-            x = 0;
-            y += 1;
-        }
-    }
-}
-
-fn wall_glyph(map : &Map, x: i32, y: i32) -> rltk::FontCharType {
-    // if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 as i32 { return 219; }
-    if x < 1 {
-        if y < 1 { return 201; }
-        if y > map.height - 2 { return 200; }
-        if is_revealed_and_wall(map, 1, y) { return 204; }
-        return 186;
-    }
-    if x > map.width - 2 {
-        if y < 1 { return 187; }
-        if y > map.height - 2 { return 188; }
-        if is_revealed_and_wall(map, map.width-2, y) { return 185; }
-        return 186;
-    }
-    if y < 1 {
-        if is_revealed_and_wall(map, x, 1) { return 203; }
-        return 205;
-    }
-    if y > map.height - 2 {
-        if is_revealed_and_wall(map, x, map.height-2) { return 202; }
-        return 205;
-    }
-
-    let mut mask : u8 = 0;
-
-    if is_revealed_and_wall(map, x,y-1) { mask += 1; } // N
-    if is_revealed_and_wall(map, x,y+1) { mask += 2; } // S
-    if is_revealed_and_wall(map, x-1,y) { mask += 4; } // W
-    if is_revealed_and_wall(map, x+1,y) { mask += 8; } // E
-
-    match mask {
-        0 => { 10 } // Pillar
-        1 => { 10 } // Wall to north
-        2 => { 10 } // wall to south
-        3 => { 186 } // wall to north and south
-        4 => { 10 } // wall to west
-        5 => { 188 } // wall to north and west
-        6 => { 187 } // wall to south and west
-        7 => { 185 } // wall to north, south and west
-        8 => { 10 } // wall to east
-        9 => { 200 } // wall to north and east
-        10 => { 201 } // wall to south and east
-        11 => { 204 } // wall to north, south and east
-        12 => { 205 } // wall to east and west
-        13 => { 202 } // wall to south, east and west
-        14 => { 203 } // wall to north, west and east
-        15 => { 206 } // wall to all directions
-        _ => { 219 } // missed one?
-    }
-}
-
-fn is_revealed_and_wall(map: &Map, x: i32, y: i32) -> bool {
-    let idx = map.xy_idx(x,y);
-    map.tiles[idx] == TileType::Wall && map.revealed_tiles[idx]
-}
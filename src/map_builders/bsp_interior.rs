@@ -1,93 +1,60 @@
 use super::{
-    Map, MapBuilder, Rect, TileType, Position,
-    spawner, apply_room_to_map,
-    SHOW_MAPGEN_VISUALIZER,
+    InitialMapBuilder, BuilderMap, Rect, TileType,
+    apply_room_to_map,
 };
 use rltk::RandomNumberGenerator;
-use specs::prelude::*;
 
 const MIN_ROOM_SIZE : i32 = 8;
 
 pub struct BspInteriorBuilder {
-    map : Map,
-    starting_position : Position,
-    depth : i32,
-    rooms : Vec<Rect>,
-    history : Vec<Map>,
     rects : Vec<Rect>,
 }
 
-impl MapBuilder for BspInteriorBuilder {
-    fn get_map(&self) -> Map { self.map.clone() }
-    fn get_starting_position(&self) -> Position { self.starting_position.clone() }
-    fn get_snapshot_history(&self) -> Vec<Map> { self.history.clone() }
-
-    fn build_map(&mut self) { self.build(); }
-
-    fn spawn_entities(&mut self, ecs : &mut World) {
-        for room in self.rooms.iter().skip(1) {
-            spawner::spawn_room(ecs, room, self.depth);
-        }
-    }
-
-    fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
-            let mut snapshot = self.map.clone();
-            for v in snapshot.revealed_tiles.iter_mut() { *v = true; }
-            self.history.push(snapshot);
-        }
+impl InitialMapBuilder for BspInteriorBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
     }
 }
 
 impl BspInteriorBuilder {
-    pub fn new(new_depth : i32) -> BspInteriorBuilder {
-        BspInteriorBuilder{
-            map : Map::new(new_depth),
-            starting_position : Position{ x: 0, y: 0 },
-            depth : new_depth,
-            rooms : Vec::new(),
-            history : Vec::new(),
+    pub fn new() -> Box<BspInteriorBuilder> {
+        Box::new(BspInteriorBuilder{
             rects : Vec::new(),
-        }
+        })
     }
 
-    fn build(&mut self) {
-        let mut rng = RandomNumberGenerator::new();
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let mut rooms : Vec<Rect> = Vec::new();
 
         self.rects.clear();
-        self.rects.push( Rect::new(1,1, self.map.width-2, self.map.height-2) );
+        self.rects.push( Rect::new(1,1, build_data.map.width-2, build_data.map.height-2) );
         let first_room = self.rects[0];
-        self.add_subrects(first_room, &mut rng);
+        self.add_subrects(first_room, rng);
 
         let rects = self.rects.clone();
         for r in rects.iter() {
             let mut room = *r;
-            while room.x2 >= self.map.width-2 { room.x2 -= 1; }
-            while room.y2 >= self.map.height-2 { room.y2 -= 1; }
-            self.rooms.push(room);
-            apply_room_to_map(&mut self.map, &room);
-            self.take_snapshot();
+            while room.x2 >= build_data.map.width-2 { room.x2 -= 1; }
+            while room.y2 >= build_data.map.height-2 { room.y2 -= 1; }
+            rooms.push(room);
+            apply_room_to_map(&mut build_data.map, &room);
+            build_data.take_snapshot();
         }
 
-        for i in 0..self.rooms.len()-1 {
-            let room = self.rooms[i];
-            let next_room = self.rooms[i+1];
+        for i in 0..rooms.len()-1 {
+            let room = rooms[i];
+            let next_room = rooms[i+1];
             let (start_x, start_y, end_x, end_y) = (
                 room.x1 + (rng.roll_dice(1, i32::abs(room.x1 - room.x2))),
                 room.y1 + (rng.roll_dice(1, i32::abs(room.y1 - room.y2))),
                 next_room.x1 + (rng.roll_dice(1, i32::abs(next_room.x1 - next_room.x2))),
                 next_room.y1 + (rng.roll_dice(1, i32::abs(next_room.y1 - next_room.y2))),
             );
-            self.draw_corridor(start_x, start_y, end_x, end_y);
-            self.take_snapshot();
+            self.draw_corridor(start_x, start_y, end_x, end_y, build_data);
+            build_data.take_snapshot();
         }
 
-        let stairs = self.rooms[self.rooms.len()-1].center();
-        let stairs_idx = self.map.xy_idx(stairs.0, stairs.1);
-        self.map.tiles[stairs_idx] = TileType::DownStairs;
-
-        let start = self.rooms[0].center();
-        self.starting_position = Position{ x: start.0, y: start.1 };
+        build_data.rooms = Some(rooms);
     }
 
     fn add_subrects(&mut self, rect : Rect, rng : &mut RandomNumberGenerator) {
@@ -115,7 +82,7 @@ impl BspInteriorBuilder {
         }
     }
 
-    fn draw_corridor(&mut self, x1:i32, y1:i32, x2:i32, y2:i32) {
+    fn draw_corridor(&mut self, x1:i32, y1:i32, x2:i32, y2:i32, build_data : &mut BuilderMap) {
         let (mut x, mut y) = (x1, y1);
 
         while x != x2 || y != y2 {
@@ -124,8 +91,8 @@ impl BspInteriorBuilder {
             else if y < y2 { y += 1; }
             else if y > y2 { y -= 1; }
 
-            let idx = self.map.xy_idx(x,y);
-            self.map.tiles[idx] = TileType::Floor;
+            let idx = build_data.map.xy_idx(x,y);
+            build_data.map.tiles[idx] = TileType::Floor;
         }
     }
 }
@@ -29,3 +29,68 @@ pub fn apply_vertical_tunnel(map : &mut Map, y1: i32, y2: i32, x: i32) {
         }
     }
 }
+
+/// Which halves of the map a digger's strokes are mirrored into
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum Symmetry { None, Horizontal, Vertical, Both }
+
+/// Digs floor at (x, y) with a square brush of `brush_size`, mirrored
+/// according to `mode`
+pub fn paint(map : &mut Map, mode : Symmetry, brush_size : i32, x : i32, y : i32) {
+    match mode {
+        Symmetry::None => apply_paint(map, brush_size, x, y),
+        Symmetry::Horizontal => {
+            let center_x = map.width / 2;
+            if x == center_x {
+                apply_paint(map, brush_size, x, y);
+            } else {
+                let dist_x = i32::abs(center_x - x);
+                apply_paint(map, brush_size, center_x + dist_x, y);
+                apply_paint(map, brush_size, center_x - dist_x, y);
+            }
+        }
+        Symmetry::Vertical => {
+            let center_y = map.height / 2;
+            if y == center_y {
+                apply_paint(map, brush_size, x, y);
+            } else {
+                let dist_y = i32::abs(center_y - y);
+                apply_paint(map, brush_size, x, center_y + dist_y);
+                apply_paint(map, brush_size, x, center_y - dist_y);
+            }
+        }
+        Symmetry::Both => {
+            let center_x = map.width / 2;
+            let center_y = map.height / 2;
+            let dist_x = i32::abs(center_x - x);
+            let dist_y = i32::abs(center_y - y);
+            apply_paint(map, brush_size, center_x + dist_x, center_y + dist_y);
+            apply_paint(map, brush_size, center_x - dist_x, center_y + dist_y);
+            apply_paint(map, brush_size, center_x + dist_x, center_y - dist_y);
+            apply_paint(map, brush_size, center_x - dist_x, center_y - dist_y);
+        }
+    }
+}
+
+//?  Never digs into the outer wall, so the map stays closed
+fn apply_paint(map : &mut Map, brush_size : i32, x : i32, y : i32) {
+    let half_brush = brush_size / 2;
+    for brush_y in y - half_brush..y - half_brush + brush_size {
+        for brush_x in x - half_brush..x - half_brush + brush_size {
+            if brush_x > 0 && brush_x < map.width - 1 && brush_y > 0 && brush_y < map.height - 1 {
+                let idx = map.xy_idx(brush_x, brush_y);
+                map.tiles[idx] = TileType::Floor;
+            }
+        }
+    }
+}
+
+/// How far every tile is from `start_idx` on foot, with `f32::MAX` where it
+/// can't be reached
+pub fn distances_from(map : &mut Map, start_idx : usize) -> Vec<f32> {
+    map.populate_blocked();
+    //?  No walk can be longer than the map has tiles, so nothing reachable is cut short
+    let max_depth = (map.width * map.height) as f32;
+    rltk::DijkstraMap::new(map.width as usize, map.height as usize, &[start_idx], &*map, max_depth).map
+}
@@ -0,0 +1,45 @@
+use rltk::RandomNumberGenerator;
+use super::{ MetaMapBuilder, BuilderMap, TileType, distances_from };
+
+/// Walls off anything the player can't walk to from the starting position,
+/// then puts the down stairs on the floor tile furthest from it. A map with
+/// no other floor in reach gets no stairs, which the map tests catch
+pub struct CullAndExit {}
+
+impl MetaMapBuilder for CullAndExit {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl CullAndExit {
+    pub fn new() -> Box<CullAndExit> {
+        Box::new(CullAndExit{})
+    }
+
+    fn build(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let starting_pos = build_data.starting_position.as_ref().expect("Culling needs a starting position").clone();
+        let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
+        let distances = distances_from(&mut build_data.map, start_idx);
+
+        let mut exit_tile : Option<(usize, f32)> = None;
+        for (i, tile) in build_data.map.tiles.iter_mut().enumerate() {
+            if !tile.walkable() { continue; }
+            if distances[i] == f32::MAX {
+                *tile = TileType::Wall;
+            } else if *tile == TileType::Floor && distances[i] > exit_tile.map_or(0.0, |(_, d)| d) {
+                exit_tile = Some((i, distances[i]));
+            }
+        }
+        //?  Anything set to spawn in the culled pockets goes with them
+        let tiles = &build_data.map.tiles;
+        build_data.spawn_list.retain(|(idx, _)| tiles[*idx] != TileType::Wall);
+        build_data.take_snapshot();
+
+        //?  Never on the start tile, where the up stairs may go
+        let Some((exit_idx, _)) = exit_tile else { return };
+        build_data.map.tiles[exit_idx] = TileType::DownStairs;
+        build_data.spawn_list.retain(|(idx, _)| *idx != exit_idx);
+        build_data.take_snapshot();
+    }
+}
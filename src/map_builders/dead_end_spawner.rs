@@ -0,0 +1,41 @@
+use rltk::RandomNumberGenerator;
+use std::collections::BTreeMap;
+use super::{ MetaMapBuilder, BuilderMap, TileType, spawner };
+
+//?  Dead ends are grouped into square sectors this wide, each filled like a room
+const SECTOR_SIZE : i32 = 16;
+
+/// Fills the dead ends of a corridor-only layout, such as a maze
+pub struct DeadEndSpawner {}
+
+impl MetaMapBuilder for DeadEndSpawner {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl DeadEndSpawner {
+    pub fn new() -> Box<DeadEndSpawner> {
+        Box::new(DeadEndSpawner{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let map = &build_data.map;
+        let start_idx = build_data.starting_position.as_ref().map(|pos| map.xy_idx(pos.x, pos.y));
+        let mut dead_ends : BTreeMap<(i32, i32), Vec<usize>> = BTreeMap::new();
+        for y in 1..map.height-1 {
+            for x in 1..map.width-1 {
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] != TileType::Floor || Some(idx) == start_idx { continue; }
+                let exits = [(x-1, y), (x+1, y), (x, y-1), (x, y+1)].iter()
+                    .filter(|(nx, ny)| map.tiles[map.xy_idx(*nx, *ny)] != TileType::Wall)
+                    .count();
+                if exits == 1 { dead_ends.entry((x / SECTOR_SIZE, y / SECTOR_SIZE)).or_default().push(idx); }
+            }
+        }
+
+        for sector in dead_ends.values() {
+            spawner::spawn_region(rng, sector, build_data.map.depth, &mut build_data.spawn_list);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use rltk::RandomNumberGenerator;
+use super::{ InitialMapBuilder, BuilderMap, TileType, Symmetry, paint };
+
+//?  Particles stuck between snapshots; one frame per particle would swamp the visualizer
+const PARTICLES_PER_SNAPSHOT : i32 = 20;
+
+/// How each particle finds its way to the cave
+#[derive(PartialEq, Copy, Clone)]
+pub enum DLAAlgorithm { WalkInwards, WalkOutwards, CentralAttractor }
+
+pub struct DLASettings {
+    pub algorithm : DLAAlgorithm,
+    pub brush_size : i32,
+    pub symmetry : Symmetry,
+    /// Particles keep coming until this share of the map is floor
+    pub floor_percent : f32,
+}
+
+/// Grows caverns by diffusion-limited aggregation: particles wander until
+/// they touch the floor dug so far, and stick where they do
+pub struct DLABuilder {
+    settings : DLASettings,
+}
+
+impl InitialMapBuilder for DLABuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl DLABuilder {
+    pub fn new(settings : DLASettings) -> Box<DLABuilder> {
+        Box::new(DLABuilder{ settings })
+    }
+
+    /// Particles start anywhere and wander until they bump into the cave
+    pub fn walk_inwards() -> Box<DLABuilder> {
+        DLABuilder::new(DLASettings{
+            algorithm : DLAAlgorithm::WalkInwards,
+            brush_size : 1,
+            symmetry : Symmetry::None,
+            floor_percent : 0.25,
+        })
+    }
+
+    /// Particles start in the middle and dig out until they break into rock
+    pub fn walk_outwards() -> Box<DLABuilder> {
+        DLABuilder::new(DLASettings{
+            algorithm : DLAAlgorithm::WalkOutwards,
+            brush_size : 2,
+            symmetry : Symmetry::None,
+            floor_percent : 0.25,
+        })
+    }
+
+    /// Particles start anywhere and head straight for the middle
+    pub fn central_attractor() -> Box<DLABuilder> {
+        DLABuilder::new(DLASettings{
+            algorithm : DLAAlgorithm::CentralAttractor,
+            brush_size : 2,
+            symmetry : Symmetry::None,
+            floor_percent : 0.25,
+        })
+    }
+
+    /// A central attractor mirrored left to right, which comes out looking
+    /// like a bug
+    pub fn insectoid() -> Box<DLABuilder> {
+        DLABuilder::new(DLASettings{
+            algorithm : DLAAlgorithm::CentralAttractor,
+            brush_size : 2,
+            symmetry : Symmetry::Horizontal,
+            floor_percent : 0.25,
+        })
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let width = build_data.map.width;
+        let height = build_data.map.height;
+        let center_x = width / 2;
+        let center_y = height / 2;
+
+        //?  A small cross in the middle for the first particles to stick to
+        for (x, y) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let idx = build_data.map.xy_idx(center_x + x, center_y + y);
+            build_data.map.tiles[idx] = TileType::Floor;
+        }
+        build_data.take_snapshot();
+
+        let total_tiles = width * height;
+        let desired_floor_tiles = (self.settings.floor_percent * total_tiles as f32) as usize;
+        let mut floor_tile_count = build_data.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count();
+        let mut particles = 0;
+
+        while floor_tile_count < desired_floor_tiles {
+            match self.settings.algorithm {
+                DLAAlgorithm::WalkInwards => {
+                    let mut digger_x = rng.roll_dice(1, width - 3) + 1;
+                    let mut digger_y = rng.roll_dice(1, height - 3) + 1;
+                    let mut prev_x = digger_x;
+                    let mut prev_y = digger_y;
+                    let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
+                    while build_data.map.tiles[digger_idx] == TileType::Wall {
+                        prev_x = digger_x;
+                        prev_y = digger_y;
+                        stagger(rng, width, height, &mut digger_x, &mut digger_y);
+                        digger_idx = build_data.map.xy_idx(digger_x, digger_y);
+                    }
+                    paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, prev_x, prev_y);
+                }
+                DLAAlgorithm::WalkOutwards => {
+                    let mut digger_x = center_x;
+                    let mut digger_y = center_y;
+                    let mut digger_idx = build_data.map.xy_idx(digger_x, digger_y);
+                    while build_data.map.tiles[digger_idx] == TileType::Floor {
+                        stagger(rng, width, height, &mut digger_x, &mut digger_y);
+                        digger_idx = build_data.map.xy_idx(digger_x, digger_y);
+                    }
+                    paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, digger_x, digger_y);
+                }
+                DLAAlgorithm::CentralAttractor => {
+                    let start = rltk::Point::new(rng.roll_dice(1, width - 3) + 1, rng.roll_dice(1, height - 3) + 1);
+                    let path = rltk::line2d(rltk::LineAlg::Bresenham, start, rltk::Point::new(center_x, center_y));
+                    let mut prev = start;
+                    for step in path.iter() {
+                        if build_data.map.tiles[build_data.map.xy_idx(step.x, step.y)] != TileType::Wall { break; }
+                        prev = *step;
+                    }
+                    paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, prev.x, prev.y);
+                }
+            }
+
+            particles += 1;
+            if particles % PARTICLES_PER_SNAPSHOT == 0 { build_data.take_snapshot(); }
+            floor_tile_count = build_data.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count();
+        }
+        build_data.take_snapshot();
+    }
+}
+
+//?  One random step, kept off the outer wall
+fn stagger(rng : &mut RandomNumberGenerator, width : i32, height : i32, x : &mut i32, y : &mut i32) {
+    match rng.roll_dice(1, 4) {
+        1 => if *x > 2 { *x -= 1; },
+        2 => if *x < width - 2 { *x += 1; },
+        3 => if *y > 2 { *y -= 1; },
+        _ => if *y < height - 2 { *y += 1; },
+    }
+}
@@ -0,0 +1,62 @@
+use rltk::RandomNumberGenerator;
+use super::{ MetaMapBuilder, BuilderMap, TileType };
+
+/// Hangs doors where corridors break through the walls around a room
+pub struct DoorPlacement {}
+
+impl MetaMapBuilder for DoorPlacement {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.doors(rng, build_data);
+    }
+}
+
+impl DoorPlacement {
+    pub fn new() -> Box<DoorPlacement> {
+        Box::new(DoorPlacement{})
+    }
+
+    /// A doorway is a floor tile in the room's wall ring with wall on both
+    /// sides of it, so the door is hung across the opening
+    fn door_possible(&self, build_data : &BuilderMap, x : i32, y : i32, vertical_wall : bool) -> bool {
+        let map = &build_data.map;
+        let idx = map.xy_idx(x, y);
+        if map.tiles[idx] != TileType::Floor { return false; }
+        if build_data.spawn_list.iter().any(|(spawn_idx, _)| *spawn_idx == idx) { return false; }
+        if let Some(start) = &build_data.starting_position {
+            if start.x == x && start.y == y { return false; }
+        }
+        let is_wall = |x : i32, y : i32| map.tiles[map.xy_idx(x, y)] == TileType::Wall;
+        if vertical_wall {
+            is_wall(x, y - 1) && is_wall(x, y + 1)
+        } else {
+            is_wall(x - 1, y) && is_wall(x + 1, y)
+        }
+    }
+
+    fn doors(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let rooms = match &build_data.rooms {
+            Some(rooms) => rooms.clone(),
+            None => panic!("Door Placement only works after rooms have been created"),
+        };
+
+        for room in rooms.iter() {
+            let mut ring : Vec<(i32, i32, bool)> = Vec::new();
+            for y in room.y1 + 1..=room.y2 {
+                ring.push((room.x1, y, true));
+                ring.push((room.x2 + 1, y, true));
+            }
+            for x in room.x1 + 1..=room.x2 {
+                ring.push((x, room.y1, false));
+                ring.push((x, room.y2 + 1, false));
+            }
+
+            for (x, y, vertical_wall) in ring {
+                if x < 1 || x > build_data.map.width - 2 || y < 1 || y > build_data.map.height - 2 { continue; }
+                if self.door_possible(build_data, x, y, vertical_wall) {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.spawn_list.push((idx, "Door".to_string()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use rltk::RandomNumberGenerator;
+use super::{ InitialMapBuilder, BuilderMap, TileType, Symmetry, paint };
+
+/// Where each new digger starts out
+#[derive(PartialEq, Copy, Clone)]
+pub enum DrunkSpawnMode { StartingPoint, Random }
+
+pub struct DrunkardSettings {
+    pub spawn_mode : DrunkSpawnMode,
+    /// How many steps a digger takes before it gives up
+    pub drunken_lifetime : i32,
+    /// Diggers keep coming until this share of the map is floor
+    pub floor_percent : f32,
+    pub brush_size : i32,
+    pub symmetry : Symmetry,
+}
+
+/// Carves winding caves by sending out diggers that stagger about at random
+pub struct DrunkardsWalkBuilder {
+    settings : DrunkardSettings,
+}
+
+impl InitialMapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(settings : DrunkardSettings) -> Box<DrunkardsWalkBuilder> {
+        Box::new(DrunkardsWalkBuilder{ settings })
+    }
+
+    /// One big cavern spreading out from the middle
+    pub fn open_area() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings{
+            spawn_mode : DrunkSpawnMode::StartingPoint,
+            drunken_lifetime : 400,
+            floor_percent : 0.5,
+            brush_size : 1,
+            symmetry : Symmetry::None,
+        })
+    }
+
+    /// Pockets all over the map, joined up where the diggers cross
+    pub fn open_halls() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings{
+            spawn_mode : DrunkSpawnMode::Random,
+            drunken_lifetime : 400,
+            floor_percent : 0.5,
+            brush_size : 1,
+            symmetry : Symmetry::None,
+        })
+    }
+
+    /// Lots of short-lived diggers, leaving narrow twisting tunnels
+    pub fn winding_passages() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings{
+            spawn_mode : DrunkSpawnMode::Random,
+            drunken_lifetime : 100,
+            floor_percent : 0.4,
+            brush_size : 1,
+            symmetry : Symmetry::None,
+        })
+    }
+
+    /// Winding passages dug two tiles wide
+    pub fn fat_passages() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings{
+            spawn_mode : DrunkSpawnMode::Random,
+            drunken_lifetime : 100,
+            floor_percent : 0.4,
+            brush_size : 2,
+            symmetry : Symmetry::None,
+        })
+    }
+
+    /// Winding passages mirrored into all four quarters of the map
+    pub fn fearful_symmetry() -> Box<DrunkardsWalkBuilder> {
+        DrunkardsWalkBuilder::new(DrunkardSettings{
+            spawn_mode : DrunkSpawnMode::Random,
+            drunken_lifetime : 100,
+            floor_percent : 0.4,
+            brush_size : 1,
+            symmetry : Symmetry::Both,
+        })
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let center_x = build_data.map.width / 2;
+        let center_y = build_data.map.height / 2;
+        let center_idx = build_data.map.xy_idx(center_x, center_y);
+        build_data.map.tiles[center_idx] = TileType::Floor;
+        build_data.take_snapshot();
+
+        let total_tiles = build_data.map.width * build_data.map.height;
+        let desired_floor_tiles = (self.settings.floor_percent * total_tiles as f32) as usize;
+        let mut floor_tile_count = build_data.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count();
+        let mut digger_count = 0;
+        //?  A cap on diggers, in case the settings ask for more floor than they can reach
+        let max_diggers = total_tiles;
+
+        while floor_tile_count < desired_floor_tiles && digger_count < max_diggers {
+            let (mut drunk_x, mut drunk_y) = match self.settings.spawn_mode {
+                DrunkSpawnMode::StartingPoint => (center_x, center_y),
+                //?  The first digger always starts in the middle, so the rest have something to find
+                DrunkSpawnMode::Random if digger_count == 0 => (center_x, center_y),
+                DrunkSpawnMode::Random => (
+                    rng.roll_dice(1, build_data.map.width - 3) + 1,
+                    rng.roll_dice(1, build_data.map.height - 3) + 1,
+                ),
+            };
+
+            let mut drunk_life = self.settings.drunken_lifetime;
+            while drunk_life > 0 {
+                paint(&mut build_data.map, self.settings.symmetry, self.settings.brush_size, drunk_x, drunk_y);
+
+                match rng.roll_dice(1, 4) {
+                    1 => if drunk_x > 2 { drunk_x -= 1; },
+                    2 => if drunk_x < build_data.map.width - 2 { drunk_x += 1; },
+                    3 => if drunk_y > 2 { drunk_y -= 1; },
+                    _ => if drunk_y < build_data.map.height - 2 { drunk_y += 1; },
+                }
+
+                drunk_life -= 1;
+            }
+
+            digger_count += 1;
+            build_data.take_snapshot();
+            floor_tile_count = build_data.map.tiles.iter().filter(|tile| **tile == TileType::Floor).count();
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use rltk::RandomNumberGenerator;
+use super::{ InitialMapBuilder, BuilderMap, TileType };
+
+//?  Cells carved between snapshots, so the visualizer shows the maze growing
+//?  without storing a frame per cell
+const CELLS_PER_SNAPSHOT : usize = 10;
+
+/// Carves a perfect maze, one with exactly one path between any two points,
+/// on a grid of cells two tiles apart
+pub struct MazeBuilder {}
+
+impl InitialMapBuilder for MazeBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl MazeBuilder {
+    pub fn new() -> Box<MazeBuilder> {
+        Box::new(MazeBuilder{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let mut grid = Grid::new((build_data.map.width - 1) / 2, (build_data.map.height - 1) / 2);
+        grid.carve(rng, build_data);
+        build_data.take_snapshot();
+    }
+}
+
+struct Grid {
+    width : i32,
+    height : i32,
+    visited : Vec<bool>,
+}
+
+impl Grid {
+    fn new(width : i32, height : i32) -> Grid {
+        Grid{ width, height, visited : vec![false; (width * height) as usize] }
+    }
+
+    fn cell_idx(&self, x : i32, y : i32) -> usize { (y * self.width + x) as usize }
+
+    /// The cell's position on the map; cells sit on odd coordinates so
+    /// there's always wall around them to knock through
+    fn tile(x : i32, y : i32) -> (i32, i32) { (x * 2 + 1, y * 2 + 1) }
+
+    fn unvisited_neighbours(&self, x : i32, y : i32) -> Vec<(i32, i32)> {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)].iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|(nx, ny)| *nx >= 0 && *nx < self.width && *ny >= 0 && *ny < self.height)
+            .filter(|(nx, ny)| !self.visited[self.cell_idx(*nx, *ny)])
+            .collect()
+    }
+
+    /// Recursive backtracker, with an explicit stack so big maps can't
+    /// overflow the real one
+    fn carve(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        if self.width < 1 || self.height < 1 { return; }
+        let mut stack : Vec<(i32, i32)> = vec![(0, 0)];
+        let start = self.cell_idx(0, 0);
+        self.visited[start] = true;
+        self.open(build_data, (0, 0), (0, 0));
+        let mut carved = 0;
+
+        while let Some(&(x, y)) = stack.last() {
+            let neighbours = self.unvisited_neighbours(x, y);
+            if neighbours.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let next = neighbours[(rng.roll_dice(1, neighbours.len() as i32) - 1) as usize];
+            let next_idx = self.cell_idx(next.0, next.1);
+            self.visited[next_idx] = true;
+            self.open(build_data, (x, y), next);
+            stack.push(next);
+
+            carved += 1;
+            if carved % CELLS_PER_SNAPSHOT == 0 { build_data.take_snapshot(); }
+        }
+    }
+
+    /// Turns cell `to` to floor, along with the wall between it and `from`
+    fn open(&self, build_data : &mut BuilderMap, from : (i32, i32), to : (i32, i32)) {
+        let (from_x, from_y) = Grid::tile(from.0, from.1);
+        let (to_x, to_y) = Grid::tile(to.0, to.1);
+        for (x, y) in [(to_x, to_y), ((from_x + to_x) / 2, (from_y + to_y) / 2)] {
+            let idx = build_data.map.xy_idx(x, y);
+            build_data.map.tiles[idx] = TileType::Floor;
+        }
+    }
+}
@@ -1,8 +1,8 @@
+use rltk::RandomNumberGenerator;
 use specs::prelude::*;
 use super::{
     Map, Rect, TileType, Position,
-    spawner,
-    SHOW_MAPGEN_VISUALIZER,
+    spawner, rex_assets,
 };
 mod simple_map;
 use simple_map::SimpleMapBuilder;
@@ -12,25 +12,232 @@ mod bsp_interior;
 use bsp_interior::BspInteriorBuilder;
 mod cellular_automata;
 use cellular_automata::CellularAutomataBuilder;
+mod room_based_spawner;
+use room_based_spawner::RoomBasedSpawner;
+mod room_based_starting_position;
+use room_based_starting_position::RoomBasedStartingPosition;
+mod area_starting_points;
+use area_starting_points::{ AreaStartingPosition, XStart, YStart };
+mod cull_and_exit;
+use cull_and_exit::CullAndExit;
+mod noise_area_spawner;
+use noise_area_spawner::NoiseAreaSpawner;
+mod door_placement;
+use door_placement::DoorPlacement;
+mod drunkard;
+use drunkard::DrunkardsWalkBuilder;
+mod maze;
+use maze::MazeBuilder;
+mod dead_end_spawner;
+use dead_end_spawner::DeadEndSpawner;
+mod dla;
+use dla::DLABuilder;
+mod voronoi;
+use voronoi::{ VoronoiCellBuilder, DistanceAlgorithm, voronoi_membership };
+mod voronoi_spawning;
+use voronoi_spawning::VoronoiSpawning;
+mod prefab_builder;
+use prefab_builder::PrefabBuilder;
+mod waveform_collapse;
+use waveform_collapse::WaveformCollapseBuilder;
+mod town;
+use town::TownBuilder;
 mod common;
 use common::*;
+pub use common::distances_from;
 
-pub trait MapBuilder {
-    fn build_map(&mut self);
-    fn spawn_entities(&mut self, ecs : &mut World);
-    fn get_map(&self) -> Map;
-    fn get_starting_position(&self) -> Position;
-    fn get_snapshot_history(&self) -> Vec<Map>;
-    fn take_snapshot(&mut self);
+/// Everything a builder chain works on, handed from one builder to the next
+pub struct BuilderMap {
+    pub spawn_list : Vec<(usize, String)>,
+    pub map : Map,
+    pub starting_position : Option<Position>,
+    pub rooms : Option<Vec<Rect>>,
+    pub history : Vec<Map>,
+    /// Whether snapshots go into `history`; only worth the copies when
+    /// something is going to play them back
+    record_history : bool,
 }
 
-pub fn random_builder(new_depth : i32) -> Box<dyn MapBuilder> {
-    let mut rng = rltk::RandomNumberGenerator::new();
-    let builder = rng.roll_dice(1,1);
-    match builder {
-        1 => Box::new(CellularAutomataBuilder::new(new_depth)),
-        2 => Box::new(BspInteriorBuilder::new(new_depth)),
-        3 => Box::new(BspDungeonBuilder::new(new_depth)),
-        _ => Box::new(SimpleMapBuilder::new(new_depth)),
+impl BuilderMap {
+    fn take_snapshot(&mut self) {
+        if self.record_history {
+            let mut snapshot = self.map.clone();
+            for v in snapshot.revealed_tiles.iter_mut() { *v = true; }
+            self.history.push(snapshot);
+        }
     }
 }
+
+/// One initial builder lays the map out, then each meta builder runs over
+/// the result in the order it was added
+pub struct BuilderChain {
+    starter : Option<Box<dyn InitialMapBuilder>>,
+    builders : Vec<Box<dyn MetaMapBuilder>>,
+    pub build_data : BuilderMap,
+}
+
+impl BuilderChain {
+    pub fn new(new_depth : i32, width : i32, height : i32, record_history : bool) -> BuilderChain {
+        BuilderChain{
+            starter : None,
+            builders : Vec::new(),
+            build_data : BuilderMap {
+                spawn_list : Vec::new(),
+                map : Map::new(new_depth, width, height),
+                starting_position : None,
+                rooms : None,
+                history : Vec::new(),
+                record_history,
+            }
+        }
+    }
+
+    pub fn start_with(&mut self, starter : Box<dyn InitialMapBuilder>) {
+        match self.starter {
+            None => self.starter = Some(starter),
+            Some(_) => panic!("You can only have one starting builder."),
+        };
+    }
+
+    pub fn with(&mut self, metabuilder : Box<dyn MetaMapBuilder>) {
+        self.builders.push(metabuilder);
+    }
+
+    pub fn build_map(&mut self, rng : &mut RandomNumberGenerator) {
+        match &mut self.starter {
+            None => panic!("Cannot run a map builder chain without a starting build system"),
+            Some(starter) => {
+                starter.build_map(rng, &mut self.build_data);
+            }
+        }
+
+        for metabuilder in self.builders.iter_mut() {
+            metabuilder.build_map(rng, &mut self.build_data);
+        }
+    }
+
+    pub fn spawn_entities(&mut self, ecs : &mut World) {
+        for entity in self.build_data.spawn_list.iter() {
+            spawner::spawn_entity(ecs, &(&entity.0, &entity.1));
+        }
+    }
+}
+
+/// Produces the layout a chain starts from
+pub trait InitialMapBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap);
+}
+
+/// Reworks or decorates whatever the builders before it produced
+pub trait MetaMapBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap);
+}
+
+/// How big the map for `depth` is; the first level fits the screen and
+/// deeper ones spread out past it
+pub fn level_size(depth : i32) -> (i32, i32) {
+    let growth = i32::min(depth - 1, 8);
+    (80 + growth * 10, 43 + growth * 5)
+}
+
+/// The chain for `new_depth`: the town on the surface, the hand-drawn
+/// levels where there is one, and a random dungeon everywhere else
+pub fn level_builder(new_depth : i32, rng : &mut RandomNumberGenerator, width : i32, height : i32, record_history : bool) -> BuilderChain {
+    if new_depth == 1 {
+        let mut builder = BuilderChain::new(new_depth, width, height, record_history);
+        builder.start_with(TownBuilder::new());
+        return builder;
+    }
+    if let Some(level) = prefab_builder::special_level(new_depth) {
+        let mut builder = BuilderChain::new(new_depth, width, height, record_history);
+        builder.start_with(PrefabBuilder::rex_level(level));
+        return builder;
+    }
+    random_builder(new_depth, rng, width, height, record_history)
+}
+
+/// Every chain `random_builder` picks between, by name
+pub const RANDOM_CHAINS : [&str; 19] = [
+    "Cellular Automata", "Open Area", "Open Halls", "Winding Passages", "Fat Passages",
+    "Fearful Symmetry", "DLA Walk Inwards", "DLA Walk Outwards", "DLA Central Attractor", "DLA Insectoid",
+    "Voronoi Pythagoras", "Voronoi Manhattan", "Voronoi Chebyshev",
+    "Wave Function Collapse", "Wave Function Collapse Warren", "Maze",
+    "BSP Interior", "BSP Dungeon", "Simple Map",
+];
+
+pub fn random_builder(new_depth : i32, rng : &mut RandomNumberGenerator, width : i32, height : i32, record_history : bool) -> BuilderChain {
+    let roll = rng.roll_dice(1, RANDOM_CHAINS.len() as i32);
+    chain_builder(roll as usize - 1, new_depth, rng, width, height, record_history)
+}
+
+/// The chain `RANDOM_CHAINS[chain]` names; every one of them finishes with
+/// the start and stairs joined up and nothing out of reach
+pub fn chain_builder(chain : usize, new_depth : i32, rng : &mut RandomNumberGenerator, width : i32, height : i32, record_history : bool) -> BuilderChain {
+    let mut builder = BuilderChain::new(new_depth, width, height, record_history);
+    match chain {
+        0..=9 => {
+            match chain {
+                0 => builder.start_with(CellularAutomataBuilder::new()),
+                1 => builder.start_with(DrunkardsWalkBuilder::open_area()),
+                2 => builder.start_with(DrunkardsWalkBuilder::open_halls()),
+                3 => builder.start_with(DrunkardsWalkBuilder::winding_passages()),
+                4 => builder.start_with(DrunkardsWalkBuilder::fat_passages()),
+                5 => builder.start_with(DrunkardsWalkBuilder::fearful_symmetry()),
+                6 => builder.start_with(DLABuilder::walk_inwards()),
+                7 => builder.start_with(DLABuilder::walk_outwards()),
+                8 => builder.start_with(DLABuilder::central_attractor()),
+                _ => builder.start_with(DLABuilder::insectoid()),
+            }
+            if rng.roll_dice(1, 3) == 1 {
+                builder.with(PrefabBuilder::sectional(prefab_builder::ORC_CAMP));
+            }
+            builder.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
+            builder.with(CullAndExit::new());
+            builder.with(NoiseAreaSpawner::new());
+            builder.with(PrefabBuilder::vaults());
+        }
+        10..=12 => {
+            match chain {
+                10 => builder.start_with(VoronoiCellBuilder::pythagoras()),
+                11 => builder.start_with(VoronoiCellBuilder::manhattan()),
+                _ => builder.start_with(VoronoiCellBuilder::chebyshev()),
+            }
+            builder.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
+            builder.with(CullAndExit::new());
+            builder.with(VoronoiSpawning::new());
+            builder.with(PrefabBuilder::vaults());
+        }
+        13 | 14 => {
+            match chain {
+                13 => {
+                    builder.start_with(CellularAutomataBuilder::new());
+                    builder.with(WaveformCollapseBuilder::new());
+                }
+                _ => builder.start_with(WaveformCollapseBuilder::from_prefab(prefab_builder::GOBLIN_WARREN.template)),
+            }
+            builder.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
+            builder.with(CullAndExit::new());
+            builder.with(VoronoiSpawning::new());
+        }
+        15 => {
+            builder.start_with(MazeBuilder::new());
+            builder.with(AreaStartingPosition::new(XStart::Left, YStart::Top));
+            builder.with(CullAndExit::new());
+            builder.with(DeadEndSpawner::new());
+        }
+        _ => {
+            match chain {
+                16 => builder.start_with(BspInteriorBuilder::new()),
+                17 => builder.start_with(BspDungeonBuilder::new()),
+                _ => builder.start_with(SimpleMapBuilder::new()),
+            }
+            builder.with(RoomBasedSpawner::new());
+            builder.with(RoomBasedStartingPosition::new());
+            builder.with(CullAndExit::new());
+            builder.with(PrefabBuilder::vaults());
+            builder.with(DoorPlacement::new());
+        }
+    }
+
+    builder
+}
@@ -0,0 +1,45 @@
+use rltk::RandomNumberGenerator;
+use std::collections::BTreeMap;
+use super::{ MetaMapBuilder, BuilderMap, TileType, spawner };
+
+/// Splits the floor into patches with cellular noise and fills each patch,
+/// for layouts that don't have rooms
+pub struct NoiseAreaSpawner {}
+
+impl MetaMapBuilder for NoiseAreaSpawner {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl NoiseAreaSpawner {
+    pub fn new() -> Box<NoiseAreaSpawner> {
+        Box::new(NoiseAreaSpawner{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let mut noise_areas : BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+        noise.set_noise_type(rltk::NoiseType::Cellular);
+        noise.set_frequency(0.08);
+        noise.set_cellular_distance_function(rltk::CellularDistanceFunction::Manhattan);
+
+        let map = &build_data.map;
+        let start_idx = build_data.starting_position.as_ref().map(|pos| map.xy_idx(pos.x, pos.y));
+        for y in 1..map.height-1 {
+            for x in 1..map.width-1 {
+                let idx = map.xy_idx(x,y);
+                if map.tiles[idx] == TileType::Floor && Some(idx) != start_idx {
+                    let cell_value_f = noise.get_noise(x as f32, y as f32) * 10240.0;
+                    let cell_value = cell_value_f as i32;
+
+                    noise_areas.entry(cell_value).or_default().push(idx);
+                }
+            }
+        }
+
+        for area in noise_areas.iter() {
+            spawner::spawn_region(rng, area.1, build_data.map.depth, &mut build_data.spawn_list);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use rltk::RandomNumberGenerator;
+use rltk::rex::XpFile;
+use std::collections::HashSet;
+use super::{ InitialMapBuilder, MetaMapBuilder, BuilderMap, Map, TileType, Position, rex_assets, distances_from };
+mod prefab_levels;
+pub use prefab_levels::*;
+mod prefab_rooms;
+pub use prefab_rooms::*;
+mod prefab_sections;
+pub use prefab_sections::*;
+
+//?  Glyphs that stand for something to spawn on a floor tile
+const GLYPH_SPAWNS : [(char, &str); 7] = [
+    ('+', "Door"),
+    ('g', "Goblin"),
+    ('o', "Orc"),
+    ('z', "Zombie"),
+    ('^', "Spike Trap"),
+    ('!', "Blood Vial"),
+    ('%', "Bloody Heart"),
+];
+
+//?  Spots tried for each vault before giving up on it
+const VAULT_ATTEMPTS : usize = 10;
+
+/// What a prefab builder does with its templates
+#[derive(PartialEq, Copy, Clone)]
+pub enum PrefabMode {
+    /// Builds the whole level from one template
+    RexLevel{ level : PrefabLevel },
+    /// Lays a template over part of an existing map
+    Sectional{ section : PrefabSection },
+    /// Stamps small rooms into open floor where they fit
+    RoomVaults,
+}
+
+/// Builds from maps drawn in REXPaint: `#` is wall, a space is floor, `@`
+/// is the start, `>` the way down, and the glyphs in `GLYPH_SPAWNS` put
+/// things on the floor
+pub struct PrefabBuilder {
+    mode : PrefabMode,
+}
+
+impl InitialMapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl MetaMapBuilder for PrefabBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+/// A template's glyphs, row by row
+struct Template {
+    width : i32,
+    height : i32,
+    glyphs : Vec<char>,
+}
+
+impl Template {
+    fn load(path : &str) -> Template {
+        rex_assets::link_prefabs();
+        let xp = XpFile::from_resource(path).unwrap_or_else(|e| panic!("Unable to load prefab {}: {}", path, e));
+        let layer = &xp.layers[0];
+        let mut glyphs = Vec::with_capacity(layer.width * layer.height);
+        for y in 0..layer.height {
+            for x in 0..layer.width {
+                let cell = layer.get(x, y).unwrap();
+                glyphs.push(rltk::to_char(cell.ch as u8));
+            }
+        }
+        Template{ width : layer.width as i32, height : layer.height as i32, glyphs }
+    }
+
+    fn glyph(&self, x : i32, y : i32) -> char { self.glyphs[(y * self.width + x) as usize] }
+}
+
+impl PrefabBuilder {
+    pub fn rex_level(level : PrefabLevel) -> Box<PrefabBuilder> {
+        Box::new(PrefabBuilder{ mode : PrefabMode::RexLevel{ level } })
+    }
+
+    pub fn sectional(section : PrefabSection) -> Box<PrefabBuilder> {
+        Box::new(PrefabBuilder{ mode : PrefabMode::Sectional{ section } })
+    }
+
+    pub fn vaults() -> Box<PrefabBuilder> {
+        Box::new(PrefabBuilder{ mode : PrefabMode::RoomVaults })
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        match self.mode {
+            PrefabMode::RexLevel{ level } => self.load_rex_level(level, build_data),
+            PrefabMode::Sectional{ section } => self.apply_sectional(section, build_data),
+            PrefabMode::RoomVaults => self.apply_room_vaults(rng, build_data),
+        }
+        build_data.take_snapshot();
+    }
+
+    fn char_to_map(&self, ch : char, x : i32, y : i32, build_data : &mut BuilderMap) {
+        let idx = build_data.map.xy_idx(x, y);
+        build_data.map.tiles[idx] = match ch {
+            '#' => TileType::Wall,
+            '>' => TileType::DownStairs,
+            _ => TileType::Floor,
+        };
+        match ch {
+            ' ' | '#' | '>' => {}
+            '@' => build_data.starting_position = Some(Position{ x, y }),
+            _ => match GLYPH_SPAWNS.iter().find(|(glyph, _)| *glyph == ch) {
+                Some((_, name)) => build_data.spawn_list.push((idx, name.to_string())),
+                None => eprintln!("Unknown glyph loading map: {}", ch),
+            },
+        }
+    }
+
+    /// Copies `template` onto the map with its top-left corner at (x, y),
+    /// replacing anything spawned underneath
+    fn stamp(&self, template : &Template, x : i32, y : i32, build_data : &mut BuilderMap) {
+        let width = build_data.map.width;
+        build_data.spawn_list.retain(|(idx, _)| {
+            let (sx, sy) = (*idx as i32 % width, *idx as i32 / width);
+            sx < x || sx >= x + template.width || sy < y || sy >= y + template.height
+        });
+        for ty in 0..template.height {
+            for tx in 0..template.width {
+                self.char_to_map(template.glyph(tx, ty), x + tx, y + ty, build_data);
+            }
+        }
+    }
+
+    fn load_rex_level(&self, level : PrefabLevel, build_data : &mut BuilderMap) {
+        let template = Template::load(level.template);
+        build_data.map = Map::new(build_data.map.depth, template.width, template.height);
+        self.stamp(&template, 0, 0, build_data);
+    }
+
+    fn apply_sectional(&self, section : PrefabSection, build_data : &mut BuilderMap) {
+        let template = Template::load(section.template);
+        let (map_width, map_height) = (build_data.map.width, build_data.map.height);
+        let x = match section.placement.0 {
+            HorizontalPlacement::Left => 0,
+            HorizontalPlacement::Center => (map_width - template.width) / 2,
+            HorizontalPlacement::Right => map_width - template.width,
+        };
+        let y = match section.placement.1 {
+            VerticalPlacement::Top => 0,
+            VerticalPlacement::Center => (map_height - template.height) / 2,
+            VerticalPlacement::Bottom => map_height - template.height,
+        };
+        self.stamp(&template, x, y, build_data);
+
+        //?  Openings in the section's edge are tunnelled straight out until they
+        //?  meet the map around it, so the section can't end up cut off
+        let mut openings : Vec<(i32, i32, i32, i32)> = Vec::new();
+        for ty in 0..template.height {
+            for tx in 0..template.width {
+                if template.glyph(tx, ty) == '#' { continue; }
+                if tx == 0 { openings.push((x + tx, y + ty, -1, 0)); }
+                if tx == template.width - 1 { openings.push((x + tx, y + ty, 1, 0)); }
+                if ty == 0 { openings.push((x + tx, y + ty, 0, -1)); }
+                if ty == template.height - 1 { openings.push((x + tx, y + ty, 0, 1)); }
+            }
+        }
+        for (mut tunnel_x, mut tunnel_y, dx, dy) in openings {
+            let mut dug : Vec<usize> = Vec::new();
+            loop {
+                tunnel_x += dx;
+                tunnel_y += dy;
+                if tunnel_x < 1 || tunnel_x > map_width - 2 || tunnel_y < 1 || tunnel_y > map_height - 2 {
+                    dug.clear();
+                    break;
+                }
+                let idx = build_data.map.xy_idx(tunnel_x, tunnel_y);
+                if build_data.map.tiles[idx] != TileType::Wall { break; }
+                dug.push(idx);
+            }
+            for idx in dug { build_data.map.tiles[idx] = TileType::Floor; }
+        }
+    }
+
+    fn apply_room_vaults(&self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let start = build_data.starting_position.as_ref().expect("Placing vaults needs a starting position").clone();
+        let start_idx = build_data.map.xy_idx(start.x, start.y);
+        let depth = build_data.map.depth;
+        let mut possible : Vec<PrefabRoom> = VAULTS.iter()
+            .filter(|vault| depth >= vault.first_depth && depth <= vault.last_depth)
+            .copied()
+            .collect();
+        let n_vaults = usize::min(rng.roll_dice(1, 3) as usize - 1, possible.len());
+        let mut used_tiles : HashSet<usize> = HashSet::new();
+
+        for _ in 0..n_vaults {
+            let vault = possible.remove((rng.roll_dice(1, possible.len() as i32) - 1) as usize);
+            let template = Template::load(vault.template);
+
+            let mut spots : Vec<(i32, i32)> = Vec::new();
+            for y in 1..build_data.map.height - template.height {
+                for x in 1..build_data.map.width - template.width {
+                    let fits = (y..y + template.height).all(|ty| (x..x + template.width).all(|tx| {
+                        let idx = build_data.map.xy_idx(tx, ty);
+                        build_data.map.tiles[idx] == TileType::Floor && idx != start_idx && !used_tiles.contains(&idx)
+                    }));
+                    if fits { spots.push((x, y)); }
+                }
+            }
+
+            let unreachable_before = unreachable_floor(&mut build_data.map, start_idx);
+            for _ in 0..usize::min(VAULT_ATTEMPTS, spots.len()) {
+                let (x, y) = spots.remove((rng.roll_dice(1, spots.len() as i32) - 1) as usize);
+                let old_tiles = build_data.map.tiles.clone();
+                let old_spawns = build_data.spawn_list.clone();
+                self.stamp(&template, x, y, build_data);
+
+                //?  Walls in a vault mustn't cut anything off that could be reached before
+                if unreachable_floor(&mut build_data.map, start_idx).is_subset(&unreachable_before) {
+                    for ty in y..y + template.height {
+                        for tx in x..x + template.width { used_tiles.insert(build_data.map.xy_idx(tx, ty)); }
+                    }
+                    break;
+                }
+                build_data.map.tiles = old_tiles;
+                build_data.spawn_list = old_spawns;
+            }
+        }
+    }
+}
+
+/// Every walkable tile that can't be reached from `start_idx`
+fn unreachable_floor(map : &mut Map, start_idx : usize) -> HashSet<usize> {
+    let distances = distances_from(map, start_idx);
+    map.tiles.iter().enumerate()
+        .filter(|(idx, tile)| tile.walkable() && distances[*idx] == f32::MAX)
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// A template's layout as a map of its own size, with everything but walls
+/// and stairs read as floor
+pub fn load_rex_map(template : &str, depth : i32) -> Map {
+    let template = Template::load(template);
+    let mut map = Map::new(depth, template.width, template.height);
+    for y in 0..template.height {
+        for x in 0..template.width {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = match template.glyph(x, y) {
+                '#' => TileType::Wall,
+                '>' => TileType::DownStairs,
+                _ => TileType::Floor,
+            };
+        }
+    }
+    map
+}
+
+/// The hand-made level that replaces the random one at `depth`, if any
+pub fn special_level(depth : i32) -> Option<PrefabLevel> {
+    SPECIAL_LEVELS.iter().find(|level| level.depth == depth).copied()
+}
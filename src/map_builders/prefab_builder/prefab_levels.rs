@@ -0,0 +1,16 @@
+/// A whole hand-made level, drawn in REXPaint
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabLevel {
+    pub template : &'static str,
+    /// The depth this level takes the place of a random one at
+    pub depth : i32,
+}
+
+/// Goblin dens around a pillared cavern, with the chieftain's orc guard
+/// between the player and the stairs
+pub const GOBLIN_WARREN : PrefabLevel = PrefabLevel{
+    template : "../resources/prefabs/goblin_warren.xp",
+    depth : 3,
+};
+
+pub const SPECIAL_LEVELS : [PrefabLevel; 1] = [GOBLIN_WARREN];
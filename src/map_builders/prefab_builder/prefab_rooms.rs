@@ -0,0 +1,30 @@
+/// A small set piece stamped into open floor
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabRoom {
+    pub template : &'static str,
+    pub first_depth : i32,
+    pub last_depth : i32,
+}
+
+/// A healing vial behind a ring of spikes
+pub const TRAP_RING : PrefabRoom = PrefabRoom{
+    template : "../resources/prefabs/trap_ring.xp",
+    first_depth : 1,
+    last_depth : 100,
+};
+
+/// Four goblins around a heart, in a hut with two ways in
+pub const GOBLIN_CAMP : PrefabRoom = PrefabRoom{
+    template : "../resources/prefabs/goblin_camp.xp",
+    first_depth : 2,
+    last_depth : 6,
+};
+
+/// Pillars in a grid with loot, traps and a pair of orcs between them
+pub const CHECKERBOARD : PrefabRoom = PrefabRoom{
+    template : "../resources/prefabs/checkerboard.xp",
+    first_depth : 4,
+    last_depth : 100,
+};
+
+pub const VAULTS : [PrefabRoom; 3] = [TRAP_RING, GOBLIN_CAMP, CHECKERBOARD];
@@ -0,0 +1,20 @@
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum HorizontalPlacement { Left, Center, Right }
+
+#[allow(dead_code)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum VerticalPlacement { Top, Center, Bottom }
+
+/// A large piece laid over one part of a generated map
+#[derive(PartialEq, Copy, Clone)]
+pub struct PrefabSection {
+    pub template : &'static str,
+    pub placement : (HorizontalPlacement, VerticalPlacement),
+}
+
+/// A walled orc camp against the east edge, with a stockade in the middle
+pub const ORC_CAMP : PrefabSection = PrefabSection{
+    template : "../resources/prefabs/orc_camp.xp",
+    placement : (HorizontalPlacement::Right, VerticalPlacement::Center),
+};
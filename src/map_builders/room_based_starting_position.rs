@@ -0,0 +1,26 @@
+use rltk::RandomNumberGenerator;
+use super::{ MetaMapBuilder, BuilderMap, Position };
+
+/// Starts the player in the centre of the first room
+pub struct RoomBasedStartingPosition {}
+
+impl MetaMapBuilder for RoomBasedStartingPosition {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl RoomBasedStartingPosition {
+    pub fn new() -> Box<RoomBasedStartingPosition> {
+        Box::new(RoomBasedStartingPosition{})
+    }
+
+    fn build(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        if let Some(rooms) = &build_data.rooms {
+            let start_pos = rooms[0].center();
+            build_data.starting_position = Some(Position{ x: start_pos.0, y: start_pos.1 });
+        } else {
+            panic!("Room Based Starting Position only works after rooms have been created");
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use rltk::RandomNumberGenerator;
+use super::{ InitialMapBuilder, BuilderMap, TileType, Position };
+
+//?  Columns of deep then shallow water along the western shore
+const DEEP_WATER : i32 = 3;
+const SHALLOW_WATER : i32 = 3;
+
+/// A building's footprint, walls included, and which side of the main road
+/// it stands on
+struct Building {
+    x : i32,
+    y : i32,
+    width : i32,
+    height : i32,
+    north_of_road : bool,
+}
+
+impl Building {
+    fn area(&self) -> i32 { (self.width - 2) * (self.height - 2) }
+
+    /// The door sits in the middle of the wall facing the road
+    fn door(&self) -> (i32, i32) {
+        let x = self.x + self.width / 2;
+        if self.north_of_road { (x, self.y + self.height - 1) } else { (x, self.y) }
+    }
+}
+
+/// Who runs a building and who's found inside it
+#[derive(Clone, Copy)]
+enum BuildingRole { Tavern, Temple, Smithy, GeneralStore, Alchemist, House }
+
+/// The surface hub: a riverside town with a road running east to the
+/// dungeon entrance, and shops and houses full of townsfolk on either side
+pub struct TownBuilder {}
+
+impl InitialMapBuilder for TownBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl TownBuilder {
+    pub fn new() -> Box<TownBuilder> {
+        Box::new(TownBuilder{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let road_y = build_data.map.height / 2;
+        self.lay_terrain(rng, build_data, road_y);
+        build_data.take_snapshot();
+
+        let buildings = self.lay_buildings(rng, build_data, road_y);
+        self.populate(rng, build_data, &buildings);
+        build_data.take_snapshot();
+
+        let shore = DEEP_WATER + SHALLOW_WATER;
+        build_data.starting_position = Some(Position{ x : shore, y : road_y });
+        self.dungeon_entrance(build_data, road_y);
+        build_data.take_snapshot();
+    }
+
+    /// Grass everywhere, the river on the west edge, a wall round the rest
+    /// and the main road from the shore to the east wall
+    fn lay_terrain(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, road_y : i32) {
+        let map = &mut build_data.map;
+        for y in 0..map.height {
+            for x in 0..map.width {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = if x < DEEP_WATER {
+                    TileType::DeepWater
+                } else if y == 0 || y == map.height - 1 || x == map.width - 1 {
+                    TileType::Wall
+                } else if x < DEEP_WATER + SHALLOW_WATER {
+                    TileType::ShallowWater
+                } else if rng.roll_dice(1, 12) == 1 {
+                    TileType::Gravel
+                } else {
+                    TileType::Grass
+                };
+            }
+        }
+
+        //?  A jetty reaches into the shallows where the road meets the river
+        for y in road_y - 1..=road_y + 1 {
+            for x in DEEP_WATER..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = if x < DEEP_WATER + SHALLOW_WATER { TileType::Bridge } else { TileType::Road };
+            }
+        }
+    }
+
+    /// Walks along each side of the road putting up buildings of random size
+    /// with gaps between them, each joined to the road by a short path
+    fn lay_buildings(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, road_y : i32) -> Vec<Building> {
+        let mut buildings : Vec<Building> = Vec::new();
+        let map_width = build_data.map.width;
+        let map_height = build_data.map.height;
+        //?  Leave the east end clear for the dungeon entrance
+        let last_x = map_width - 10;
+
+        for north_of_road in [true, false] {
+            let mut x = DEEP_WATER + SHALLOW_WATER + 3 + rng.roll_dice(1, 3);
+            loop {
+                let width = rng.roll_dice(1, 6) + 6;
+                if x + width > last_x { break; }
+                let max_height = if north_of_road { road_y - 4 } else { map_height - road_y - 5 };
+                let height = i32::min(rng.roll_dice(1, 5) + 5, max_height);
+                let y = if north_of_road { road_y - 3 - height } else { road_y + 3 };
+                let building = Building{ x, y, width, height, north_of_road };
+                self.raise(build_data, &building, road_y);
+                buildings.push(building);
+                build_data.take_snapshot();
+                x += width + rng.roll_dice(1, 3) + 1;
+            }
+        }
+
+        buildings
+    }
+
+    fn raise(&mut self, build_data : &mut BuilderMap, building : &Building, road_y : i32) {
+        let map = &mut build_data.map;
+        for y in building.y..building.y + building.height {
+            for x in building.x..building.x + building.width {
+                let edge = x == building.x || x == building.x + building.width - 1
+                    || y == building.y || y == building.y + building.height - 1;
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = if edge { TileType::Wall } else { TileType::WoodFloor };
+            }
+        }
+
+        let (door_x, door_y) = building.door();
+        let door_idx = map.xy_idx(door_x, door_y);
+        map.tiles[door_idx] = TileType::WoodFloor;
+        build_data.spawn_list.push((door_idx, "Door".to_string()));
+
+        let (from, to) = if building.north_of_road { (door_y + 1, road_y - 2) } else { (road_y + 2, door_y - 1) };
+        for y in from..=to {
+            let idx = map.xy_idx(door_x, y);
+            map.tiles[idx] = TileType::Road;
+        }
+    }
+
+    /// The biggest buildings become the town's businesses, in order of how
+    /// much room they need; the rest are homes
+    fn populate(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, buildings : &[Building]) {
+        let mut by_size : Vec<usize> = (0..buildings.len()).collect();
+        by_size.sort_by(|a, b| buildings[*b].area().cmp(&buildings[*a].area()));
+
+        let businesses = [
+            BuildingRole::Tavern, BuildingRole::Temple, BuildingRole::Smithy,
+            BuildingRole::GeneralStore, BuildingRole::Alchemist,
+        ];
+        for (rank, building_idx) in by_size.iter().enumerate() {
+            let role = if rank < businesses.len() { businesses[rank] } else { BuildingRole::House };
+            let occupants : Vec<&str> = match role {
+                BuildingRole::Tavern => {
+                    let mut folk = vec!["Barkeep"];
+                    for _ in 0..rng.roll_dice(1, 3) + 1 {
+                        folk.push(if rng.roll_dice(1, 3) == 1 { "Drunk" } else { "Townsperson" });
+                    }
+                    folk
+                }
+                BuildingRole::Temple => vec!["Priest", "Townsperson"],
+                BuildingRole::Smithy => vec!["Blacksmith"],
+                BuildingRole::GeneralStore => vec!["Shopkeeper"],
+                BuildingRole::Alchemist => vec!["Alchemist"],
+                BuildingRole::House => vec!["Townsperson"; rng.roll_dice(1, 3) as usize - 1],
+            };
+            self.place_inside(rng, build_data, &buildings[*building_idx], &occupants);
+        }
+    }
+
+    /// Drops each occupant on a free floor tile inside the building
+    fn place_inside(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, building : &Building, occupants : &[&str]) {
+        let mut free : Vec<usize> = Vec::new();
+        for y in building.y + 1..building.y + building.height - 1 {
+            for x in building.x + 1..building.x + building.width - 1 {
+                free.push(build_data.map.xy_idx(x, y));
+            }
+        }
+
+        for name in occupants {
+            if free.is_empty() { return; }
+            let idx = free.remove((rng.roll_dice(1, free.len() as i32) - 1) as usize);
+            build_data.spawn_list.push((idx, name.to_string()));
+        }
+    }
+
+    /// The way down is at the east end of the road, in a gravel clearing
+    fn dungeon_entrance(&mut self, build_data : &mut BuilderMap, road_y : i32) {
+        let map = &mut build_data.map;
+        let stairs_x = map.width - 4;
+        for y in road_y - 2..=road_y + 2 {
+            for x in stairs_x - 2..map.width - 1 {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::Gravel;
+            }
+        }
+        let stairs_idx = map.xy_idx(stairs_x, road_y);
+        map.tiles[stairs_idx] = TileType::DownStairs;
+    }
+}
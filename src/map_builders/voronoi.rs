@@ -0,0 +1,113 @@
+use rltk::RandomNumberGenerator;
+use std::collections::BTreeMap;
+use super::{ InitialMapBuilder, BuilderMap, TileType };
+
+/// How distance to a seed is measured, which decides the shape of the cells
+#[derive(PartialEq, Copy, Clone)]
+pub enum DistanceAlgorithm { Pythagoras, Manhattan, Chebyshev }
+
+/// Scatters `n_seeds` seeds over a `width` by `height` map and returns, for
+/// every tile, the index of the seed closest to it
+pub fn voronoi_membership(rng : &mut RandomNumberGenerator, width : i32, height : i32, n_seeds : usize, distance : DistanceAlgorithm) -> Vec<usize> {
+    let mut seeds : Vec<rltk::Point> = Vec::new();
+    let n_seeds = usize::min(n_seeds, ((width - 2) * (height - 2)) as usize);
+    while seeds.len() < n_seeds {
+        let seed = rltk::Point::new(rng.roll_dice(1, width - 2), rng.roll_dice(1, height - 2));
+        if !seeds.contains(&seed) { seeds.push(seed); }
+    }
+
+    let alg = match distance {
+        DistanceAlgorithm::Pythagoras => rltk::DistanceAlg::PythagorasSquared,
+        DistanceAlgorithm::Manhattan => rltk::DistanceAlg::Manhattan,
+        DistanceAlgorithm::Chebyshev => rltk::DistanceAlg::Chebyshev,
+    };
+    let mut membership = vec![0; (width * height) as usize];
+    for (idx, member) in membership.iter_mut().enumerate() {
+        let tile = rltk::Point::new(idx as i32 % width, idx as i32 / width);
+        let mut closest = (0, f32::MAX);
+        for (seed_idx, seed) in seeds.iter().enumerate() {
+            let dist = alg.distance2d(tile, *seed);
+            if dist < closest.1 { closest = (seed_idx, dist); }
+        }
+        *member = closest.0;
+    }
+    membership
+}
+
+//?  Roughly one cell per this many tiles, so bigger maps get more cells
+//?  rather than bigger ones
+const TILES_PER_CELL : i32 = 100;
+
+/// Splits the map into Voronoi cells, walls the cells off from each other
+/// and knocks one gap through the wall between each pair of neighbours
+pub struct VoronoiCellBuilder {
+    distance : DistanceAlgorithm,
+}
+
+impl InitialMapBuilder for VoronoiCellBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl VoronoiCellBuilder {
+    pub fn new(distance : DistanceAlgorithm) -> Box<VoronoiCellBuilder> {
+        Box::new(VoronoiCellBuilder{ distance })
+    }
+
+    /// Round-ish cells
+    pub fn pythagoras() -> Box<VoronoiCellBuilder> { VoronoiCellBuilder::new(DistanceAlgorithm::Pythagoras) }
+    /// Diamond-ish cells with diagonal walls
+    pub fn manhattan() -> Box<VoronoiCellBuilder> { VoronoiCellBuilder::new(DistanceAlgorithm::Manhattan) }
+    /// Boxy cells
+    pub fn chebyshev() -> Box<VoronoiCellBuilder> { VoronoiCellBuilder::new(DistanceAlgorithm::Chebyshev) }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let width = build_data.map.width;
+        let height = build_data.map.height;
+        let n_seeds = usize::max(1, (width * height / TILES_PER_CELL) as usize);
+        let membership = voronoi_membership(rng, width, height, n_seeds, self.distance);
+
+        //?  A tile becomes wall if a later tile next to it, diagonals included,
+        //?  is in another cell; that keeps walls thin but still stops anyone
+        //?  stepping diagonally between cells
+        let w = width as usize;
+        for y in 1..height-1 {
+            for x in 1..width-1 {
+                let idx = build_data.map.xy_idx(x, y);
+                let my_cell = membership[idx];
+                let on_boundary = [idx + 1, idx + w - 1, idx + w, idx + w + 1].iter().any(|n| membership[*n] != my_cell);
+                build_data.map.tiles[idx] = if on_boundary { TileType::Wall } else { TileType::Floor };
+            }
+        }
+        build_data.take_snapshot();
+
+        //?  Walls come out one or two tiles thick, so a gap is a straight run
+        //?  of up to two wall tiles with a different cell's floor at each end
+        let mut shared_walls : BTreeMap<(usize, usize), Vec<Vec<usize>>> = BTreeMap::new();
+        for y in 1..height-1 {
+            for x in 1..width-1 {
+                let idx = build_data.map.xy_idx(x, y);
+                for (step, room) in [(1, width - 1 - x), (w, height - 1 - y)] {
+                    for thickness in 1..=usize::min(2, room as usize) {
+                        let tiles = &build_data.map.tiles;
+                        let a = idx - step;
+                        let b = idx + thickness * step;
+                        let run : Vec<usize> = (0..thickness).map(|i| idx + i * step).collect();
+                        if tiles[a] == TileType::Floor && tiles[b] == TileType::Floor && membership[a] != membership[b]
+                            && run.iter().all(|t| tiles[*t] == TileType::Wall)
+                        {
+                            let pair = (usize::min(membership[a], membership[b]), usize::max(membership[a], membership[b]));
+                            shared_walls.entry(pair).or_default().push(run);
+                        }
+                    }
+                }
+            }
+        }
+        for gaps in shared_walls.values() {
+            let gap = &gaps[(rng.roll_dice(1, gaps.len() as i32) - 1) as usize];
+            for idx in gap.iter() { build_data.map.tiles[*idx] = TileType::Floor; }
+        }
+        build_data.take_snapshot();
+    }
+}
@@ -0,0 +1,42 @@
+use rltk::RandomNumberGenerator;
+use std::collections::BTreeMap;
+use super::{ MetaMapBuilder, BuilderMap, TileType, spawner, voronoi_membership, DistanceAlgorithm };
+
+//?  Roughly one spawn zone per this many tiles of map
+const TILES_PER_ZONE : i32 = 110;
+
+/// Divides the floor into Voronoi cells and fills each cell like a room, so
+/// any layout can be zoned for spawning whether or not it has rooms
+pub struct VoronoiSpawning {}
+
+impl MetaMapBuilder for VoronoiSpawning {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl VoronoiSpawning {
+    pub fn new() -> Box<VoronoiSpawning> {
+        Box::new(VoronoiSpawning{})
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let width = build_data.map.width;
+        let height = build_data.map.height;
+        let n_zones = usize::max(1, (width * height / TILES_PER_ZONE) as usize);
+        let membership = voronoi_membership(rng, width, height, n_zones, DistanceAlgorithm::Pythagoras);
+
+        let map = &build_data.map;
+        let start_idx = build_data.starting_position.as_ref().map(|pos| map.xy_idx(pos.x, pos.y));
+        let mut zones : BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (idx, tile) in map.tiles.iter().enumerate() {
+            if *tile == TileType::Floor && Some(idx) != start_idx {
+                zones.entry(membership[idx]).or_default().push(idx);
+            }
+        }
+
+        for zone in zones.values() {
+            spawner::spawn_region(rng, zone, build_data.map.depth, &mut build_data.spawn_list);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::TileType;
+
+/// Edges of a chunk, in the order `MapChunk` stores them
+pub const NORTH : usize = 0;
+pub const SOUTH : usize = 1;
+pub const WEST : usize = 2;
+pub const EAST : usize = 3;
+
+/// The edge on the other side of a shared border
+pub fn opposite(direction : usize) -> usize {
+    match direction {
+        NORTH => SOUTH,
+        SOUTH => NORTH,
+        WEST => EAST,
+        _ => WEST,
+    }
+}
+
+/// One square pattern learned from the source map, and what may sit next to it
+#[derive(Clone)]
+pub struct MapChunk {
+    pub pattern : Vec<TileType>,
+    /// For each edge, which of its tiles are open floor
+    pub exits : [Vec<bool>; 4],
+    /// For each edge, the patterns allowed on the far side of it
+    pub compatible_with : [Vec<usize>; 4],
+}
+
+pub fn tile_idx_in_chunk(chunk_size : i32, x : i32, y : i32) -> usize {
+    ((y * chunk_size) + x) as usize
+}
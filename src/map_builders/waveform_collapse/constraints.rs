@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use super::{ Map, TileType, MapChunk, tile_idx_in_chunk, NORTH, SOUTH, WEST, EAST, opposite };
+
+/// Cuts the map into `chunk_size` squares and returns each distinct pattern,
+/// along with its mirror images if `include_flipping` is set
+pub fn build_patterns(map : &Map, chunk_size : i32, include_flipping : bool) -> Vec<Vec<TileType>> {
+    let chunks_x = map.width / chunk_size;
+    let chunks_y = map.height / chunk_size;
+    let mut seen : HashSet<Vec<TileType>> = HashSet::new();
+    let mut patterns : Vec<Vec<TileType>> = Vec::new();
+
+    for cy in 0..chunks_y {
+        for cx in 0..chunks_x {
+            let flips : &[(bool, bool)] = if include_flipping {
+                &[(false, false), (true, false), (false, true), (true, true)]
+            } else {
+                &[(false, false)]
+            };
+            for (flip_x, flip_y) in flips.iter() {
+                let mut pattern : Vec<TileType> = Vec::with_capacity((chunk_size * chunk_size) as usize);
+                for y in 0..chunk_size {
+                    for x in 0..chunk_size {
+                        let src_x = cx * chunk_size + if *flip_x { chunk_size - 1 - x } else { x };
+                        let src_y = cy * chunk_size + if *flip_y { chunk_size - 1 - y } else { y };
+                        //?  Stairs are placed again later, so they're learned as floor
+                        pattern.push(match map.tiles[map.xy_idx(src_x, src_y)] {
+                            TileType::Wall => TileType::Wall,
+                            _ => TileType::Floor,
+                        });
+                    }
+                }
+                if seen.insert(pattern.clone()) { patterns.push(pattern); }
+            }
+        }
+    }
+
+    patterns
+}
+
+/// Works out each pattern's exits and which patterns can border it
+pub fn patterns_to_constraints(patterns : Vec<Vec<TileType>>, chunk_size : i32) -> Vec<MapChunk> {
+    let mut constraints : Vec<MapChunk> = patterns.into_iter().map(|pattern| {
+        let mut exits : [Vec<bool>; 4] = Default::default();
+        for i in 0..chunk_size {
+            exits[NORTH].push(pattern[tile_idx_in_chunk(chunk_size, i, 0)] == TileType::Floor);
+            exits[SOUTH].push(pattern[tile_idx_in_chunk(chunk_size, i, chunk_size - 1)] == TileType::Floor);
+            exits[WEST].push(pattern[tile_idx_in_chunk(chunk_size, 0, i)] == TileType::Floor);
+            exits[EAST].push(pattern[tile_idx_in_chunk(chunk_size, chunk_size - 1, i)] == TileType::Floor);
+        }
+        MapChunk{ pattern, exits, compatible_with : Default::default() }
+    }).collect();
+
+    for i in 0..constraints.len() {
+        for direction in [NORTH, SOUTH, WEST, EAST] {
+            let compatible : Vec<usize> = (0..constraints.len())
+                .filter(|j| edges_compatible(&constraints[i].exits[direction], &constraints[*j].exits[opposite(direction)]))
+                .collect();
+            constraints[i].compatible_with[direction] = compatible;
+        }
+    }
+
+    constraints
+}
+
+//?  Two edges fit if both are solid wall, or they share at least one open
+//?  tile so the way through lines up
+fn edges_compatible(a : &[bool], b : &[bool]) -> bool {
+    let a_open = a.iter().any(|open| *open);
+    let b_open = b.iter().any(|open| *open);
+    if !a_open && !b_open { return true; }
+    a.iter().zip(b.iter()).any(|(a, b)| *a && *b)
+}
+
+pub fn render_pattern_to_map(map : &mut Map, chunk : &MapChunk, chunk_size : i32, start_x : i32, start_y : i32) {
+    for y in 0..chunk_size {
+        for x in 0..chunk_size {
+            let idx = map.xy_idx(start_x + x, start_y + y);
+            map.tiles[idx] = chunk.pattern[tile_idx_in_chunk(chunk_size, x, y)];
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use rltk::RandomNumberGenerator;
+use super::{ InitialMapBuilder, MetaMapBuilder, BuilderMap, Map, TileType, prefab_builder };
+mod common;
+use common::*;
+mod constraints;
+use constraints::*;
+mod solver;
+use solver::*;
+
+const CHUNK_SIZE : i32 = 8;
+//?  Fresh starts allowed after contradictions before settling for the source map
+const MAX_ATTEMPTS : i32 = 10;
+
+/// Where the patterns come from
+#[derive(PartialEq, Copy, Clone)]
+pub enum WaveformSource {
+    /// Whatever the builders before this one produced
+    CurrentMap,
+    /// A REXPaint template
+    Prefab{ template : &'static str },
+}
+
+/// Learns which chunks of a source map sit next to which, then lays out a
+/// new map of the same size that follows the same rules
+pub struct WaveformCollapseBuilder {
+    source : WaveformSource,
+}
+
+impl InitialMapBuilder for WaveformCollapseBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl MetaMapBuilder for WaveformCollapseBuilder {
+    fn build_map(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        self.build(rng, build_data);
+    }
+}
+
+impl WaveformCollapseBuilder {
+    /// Reworks the map built so far
+    pub fn new() -> Box<WaveformCollapseBuilder> {
+        Box::new(WaveformCollapseBuilder{ source : WaveformSource::CurrentMap })
+    }
+
+    /// Builds a map in the style of a hand-drawn template
+    pub fn from_prefab(template : &'static str) -> Box<WaveformCollapseBuilder> {
+        Box::new(WaveformCollapseBuilder{ source : WaveformSource::Prefab{ template } })
+    }
+
+    fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        let (width, height, depth) = (build_data.map.width, build_data.map.height, build_data.map.depth);
+        let source = match self.source {
+            WaveformSource::CurrentMap => build_data.map.clone(),
+            WaveformSource::Prefab{ template } => prefab_builder::load_rex_map(template, depth),
+        };
+        let patterns = build_patterns(&source, CHUNK_SIZE, true);
+        let constraints = patterns_to_constraints(patterns, CHUNK_SIZE);
+
+        //?  Nothing from the old layout survives, so neither does anything placed on it
+        build_data.spawn_list.clear();
+        build_data.starting_position = None;
+        build_data.rooms = None;
+
+        for _attempt in 0..MAX_ATTEMPTS {
+            build_data.map = Map::new(depth, width, height);
+            let mut solver = Solver::new(constraints.clone(), CHUNK_SIZE, &build_data.map);
+            while !solver.iteration(&mut build_data.map, rng) {
+                build_data.take_snapshot();
+            }
+            if solver.possible {
+                seal_edges(&mut build_data.map);
+                build_data.take_snapshot();
+                return;
+            }
+        }
+
+        build_data.map = source;
+        build_data.map.depth = depth;
+        build_data.take_snapshot();
+    }
+}
+
+//?  Chunks are copied from anywhere in the source, edges included, so the
+//?  border has to be walled up again afterwards
+fn seal_edges(map : &mut Map) {
+    for x in 0..map.width {
+        let (top, bottom) = (map.xy_idx(x, 0), map.xy_idx(x, map.height - 1));
+        map.tiles[top] = TileType::Wall;
+        map.tiles[bottom] = TileType::Wall;
+    }
+    for y in 0..map.height {
+        let (left, right) = (map.xy_idx(0, y), map.xy_idx(map.width - 1, y));
+        map.tiles[left] = TileType::Wall;
+        map.tiles[right] = TileType::Wall;
+    }
+}
@@ -0,0 +1,87 @@
+use rltk::RandomNumberGenerator;
+use super::{ Map, MapChunk, render_pattern_to_map, NORTH, SOUTH, WEST, EAST };
+
+/// Fills a grid of chunks one at a time, always picking the chunk with the
+/// fewest patterns that still fit its placed neighbours
+pub struct Solver {
+    constraints : Vec<MapChunk>,
+    chunk_size : i32,
+    chunks : Vec<Option<usize>>,
+    chunks_x : i32,
+    chunks_y : i32,
+    /// False once some chunk has no pattern left that fits
+    pub possible : bool,
+}
+
+impl Solver {
+    pub fn new(constraints : Vec<MapChunk>, chunk_size : i32, map : &Map) -> Solver {
+        let chunks_x = map.width / chunk_size;
+        let chunks_y = map.height / chunk_size;
+        Solver{
+            constraints,
+            chunk_size,
+            chunks : vec![None; (chunks_x * chunks_y) as usize],
+            chunks_x,
+            chunks_y,
+            possible : true,
+        }
+    }
+
+    fn chunk_idx(&self, x : i32, y : i32) -> usize { ((y * self.chunks_x) + x) as usize }
+
+    /// The patterns that fit every placed neighbour of chunk (x, y), and
+    /// whether it has any placed neighbours at all
+    fn options(&self, x : i32, y : i32) -> (Vec<usize>, bool) {
+        let mut options : Vec<usize> = (0..self.constraints.len()).collect();
+        let mut constrained = false;
+        //?  Each neighbour and the edge of it that faces this chunk
+        let neighbours = [(x, y - 1, SOUTH), (x, y + 1, NORTH), (x - 1, y, EAST), (x + 1, y, WEST)];
+        for (nx, ny, facing) in neighbours {
+            if nx < 0 || nx >= self.chunks_x || ny < 0 || ny >= self.chunks_y { continue; }
+            if let Some(placed) = self.chunks[self.chunk_idx(nx, ny)] {
+                constrained = true;
+                let allowed = &self.constraints[placed].compatible_with[facing];
+                options.retain(|option| allowed.contains(option));
+            }
+        }
+        (options, constrained)
+    }
+
+    /// Places the next chunk; returns true once every chunk is placed or a
+    /// contradiction has been hit (check `possible`)
+    pub fn iteration(&mut self, map : &mut Map, rng : &mut RandomNumberGenerator) -> bool {
+        let mut best : Vec<(usize, Vec<usize>)> = Vec::new();
+        let mut any_constrained = false;
+        for y in 0..self.chunks_y {
+            for x in 0..self.chunks_x {
+                let idx = self.chunk_idx(x, y);
+                if self.chunks[idx].is_some() { continue; }
+                let (options, constrained) = self.options(x, y);
+                //?  Chunks next to placed ones go first, so the map grows outwards
+                if any_constrained && !constrained { continue; }
+                if constrained && !any_constrained {
+                    any_constrained = true;
+                    best.clear();
+                }
+                if options.is_empty() {
+                    self.possible = false;
+                    return true;
+                }
+                match best.first() {
+                    Some((_, fewest)) if fewest.len() < options.len() => {}
+                    Some((_, fewest)) if fewest.len() == options.len() => best.push((idx, options)),
+                    _ => { best.clear(); best.push((idx, options)); }
+                }
+            }
+        }
+        if best.is_empty() { return true; }
+
+        let (idx, options) = &best[(rng.roll_dice(1, best.len() as i32) - 1) as usize];
+        let pattern = options[(rng.roll_dice(1, options.len() as i32) - 1) as usize];
+        self.chunks[*idx] = Some(pattern);
+        let x = *idx as i32 % self.chunks_x;
+        let y = *idx as i32 / self.chunks_x;
+        render_pattern_to_map(map, &self.constraints[pattern], self.chunk_size, x * self.chunk_size, y * self.chunk_size);
+        false
+    }
+}
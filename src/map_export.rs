@@ -0,0 +1,274 @@
+use rltk::{ RGB, FontCharType };
+use specs::prelude::*;
+use std::io::Write;
+use std::path::Path;
+use super::{ Map, TileType, Position, Renderable, Hidden, Name, Player, camera, rng, headless::Simulation, run_systems, goto_next_level };
+
+//?  The font every export is drawn with, built into rltk
+const FONT_PATH : &str = "resources/terminal8x8.png";
+const FONT_CELL : u32 = 8;
+
+/// Where the in-game export key writes to
+pub const EXPORT_DIR : &str = "./exports";
+
+/// The file types a map can be exported as
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ExportFormat {
+    /// One ASCII character per tile
+    Text,
+    /// The game's CP437 glyphs as Unicode, with 24-bit terminal colours
+    Ansi,
+    /// The map drawn with the game's font, 8x8 pixels a tile
+    Png,
+}
+
+impl ExportFormat {
+    pub const ALL : [ExportFormat; 3] = [ExportFormat::Text, ExportFormat::Ansi, ExportFormat::Png];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Text => "txt",
+            ExportFormat::Ansi => "ans",
+            ExportFormat::Png => "png",
+        }
+    }
+
+    pub fn from_name(name : &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "text" | "txt" => Some(ExportFormat::Text),
+            "ansi" | "ans" => Some(ExportFormat::Ansi),
+            "png" => Some(ExportFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// What goes into an export besides the bare tiles
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ExportOptions {
+    /// Draw whatever is standing on the map
+    pub entities : bool,
+    /// Only draw what the player has seen, and only the entities in view
+    pub revealed_only : bool,
+    pub bloodstains : bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> ExportOptions {
+        ExportOptions{ entities : true, revealed_only : false, bloodstains : true }
+    }
+}
+
+/// One map tile as it's exported; `ascii` stands in for the glyph in plain text
+#[derive(Copy, Clone)]
+struct Cell {
+    glyph : FontCharType,
+    ascii : char,
+    fg : RGB,
+    bg : RGB,
+}
+
+const BLANK : Cell = Cell{ glyph : 0, ascii : ' ', fg : RGB{ r : 0.0, g : 0.0, b : 0.0 }, bg : RGB{ r : 0.0, g : 0.0, b : 0.0 } };
+
+/// The current level laid out cell by cell, the way the camera would draw it
+/// if the whole map fitted on screen
+struct Grid {
+    width : i32,
+    height : i32,
+    cells : Vec<Cell>,
+}
+
+impl Grid {
+    fn new(ecs : &World, options : ExportOptions) -> Grid {
+        let mut map = Map::clone(&ecs.fetch::<Map>());
+        if !options.bloodstains { map.bloodstains.clear(); }
+        //?  Everything is drawn lit; revealed-only still hides what was never seen
+        let revealed = map.revealed_tiles.clone();
+        if !options.revealed_only {
+            for v in map.revealed_tiles.iter_mut() { *v = true; }
+        }
+        let visible = map.visible_tiles.clone();
+        for v in map.visible_tiles.iter_mut() { *v = true; }
+
+        let mut cells = vec![BLANK; map.tiles.len()];
+        for (idx, cell) in cells.iter_mut().enumerate() {
+            if !map.revealed_tiles[idx] { continue; }
+            let (glyph, fg, bg) = camera::tile_glyph(idx, &map);
+            let ascii = if map.bloodstains.contains(&idx) { '%' } else { tile_ascii(map.tiles[idx]) };
+            *cell = Cell{ glyph, ascii, fg, bg };
+        }
+
+        if options.entities {
+            let positions = ecs.read_storage::<Position>();
+            let renderables = ecs.read_storage::<Renderable>();
+            let names = ecs.read_storage::<Name>();
+            let hidden = ecs.read_storage::<Hidden>();
+            let players = ecs.read_storage::<Player>();
+            let mut data = (&positions, &renderables, names.maybe(), players.maybe(), !&hidden).join().collect::<Vec<_>>();
+            data.sort_by_key(|d| std::cmp::Reverse(d.1.render_order));
+            for (pos, render, name, player, _hidden) in data {
+                if pos.x < 0 || pos.x >= map.width || pos.y < 0 || pos.y >= map.height { continue; }
+                let idx = map.xy_idx(pos.x, pos.y);
+                if options.revealed_only && !(revealed[idx] && visible[idx]) { continue; }
+                //?  Whatever the player looks like in game, they're an @ in plain text
+                let ascii = if player.is_some() { '@' } else { entity_ascii(render.glyph, name.map(|n| n.name.as_str())) };
+                cells[idx] = Cell{ glyph : render.glyph, ascii, fg : render.fg, bg : render.bg };
+            }
+        }
+
+        Grid{ width : map.width, height : map.height, cells }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width as usize)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for row in self.rows() {
+            text.extend(row.iter().map(|c| c.ascii));
+            text.push('\n');
+        }
+        text
+    }
+
+    fn to_ansi(&self) -> String {
+        let mut text = String::new();
+        for row in self.rows() {
+            for cell in row {
+                let (fg, bg) = (rgb_bytes(cell.fg), rgb_bytes(cell.bg));
+                let glyph = if cell.glyph == 0 { ' ' } else { rltk::to_char(cell.glyph as u8) };
+                text.push_str(&format!("\x1b[38;2;{};{};{};48;2;{};{};{}m{}", fg[0], fg[1], fg[2], bg[0], bg[1], bg[2], glyph));
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+
+    fn to_png(&self) -> Result<Vec<u8>, String> {
+        let font_bytes = rltk::EMBED.lock().get_resource(FONT_PATH.to_string())
+            .ok_or_else(|| format!("The {} font isn't built in", FONT_PATH))?;
+        let font = image::load_from_memory(font_bytes).map_err(|e| e.to_string())?.to_rgba8();
+
+        let mut img = image::RgbImage::new(self.width as u32 * FONT_CELL, self.height as u32 * FONT_CELL);
+        for (idx, cell) in self.cells.iter().enumerate() {
+            let (cx, cy) = ((idx as i32 % self.width) as u32 * FONT_CELL, (idx as i32 / self.width) as u32 * FONT_CELL);
+            //?  The font is a 16x16 sheet of glyphs in code page order
+            let (gx, gy) = ((cell.glyph as u32 % 16) * FONT_CELL, (cell.glyph as u32 / 16) * FONT_CELL);
+            for y in 0..FONT_CELL {
+                for x in 0..FONT_CELL {
+                    let px = font.get_pixel(gx + x, gy + y);
+                    let lit = px[3] > 127 && (px[0] as u32 + px[1] as u32 + px[2] as u32) > 381;
+                    let colour = if lit && cell.glyph != 0 { cell.fg } else { cell.bg };
+                    img.put_pixel(cx + x, cy + y, image::Rgb(rgb_bytes(colour)));
+                }
+            }
+        }
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageOutputFormat::Png).map_err(|e| e.to_string())?;
+        Ok(png.into_inner())
+    }
+}
+
+fn rgb_bytes(colour : RGB) -> [u8; 3] {
+    [(colour.r * 255.0) as u8, (colour.g * 255.0) as u8, (colour.b * 255.0) as u8]
+}
+
+fn tile_ascii(tile : TileType) -> char {
+    match tile {
+        TileType::Wall => '#',
+        TileType::Floor => '.',
+        TileType::DownStairs => '>',
+        TileType::UpStairs => '<',
+        TileType::Road => '=',
+        TileType::Grass => '"',
+        TileType::Gravel => ',',
+        TileType::WoodFloor => '_',
+        TileType::Bridge => ':',
+        TileType::ShallowWater => '-',
+        TileType::DeepWater => '~',
+    }
+}
+
+//?  Glyphs outside ASCII fall back to the first letter of the entity's name
+fn entity_ascii(glyph : FontCharType, name : Option<&str>) -> char {
+    let c = rltk::to_char(glyph as u8);
+    if c.is_ascii_graphic() { return c; }
+    name.and_then(|n| n.chars().next()).unwrap_or('?')
+}
+
+/// The current level as a file of the given format
+pub fn export_map(ecs : &World, format : ExportFormat, options : ExportOptions) -> Result<Vec<u8>, String> {
+    let grid = Grid::new(ecs, options);
+    match format {
+        ExportFormat::Text => Ok(grid.to_text().into_bytes()),
+        ExportFormat::Ansi => Ok(grid.to_ansi().into_bytes()),
+        ExportFormat::Png => grid.to_png(),
+    }
+}
+
+pub fn export_map_to_file(ecs : &World, path : &Path, format : ExportFormat, options : ExportOptions) -> Result<(), String> {
+    let bytes = export_map(ecs, format, options)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(path, bytes).map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+/// Writes the current level in every format, named after the run's seed,
+/// the depth and the turn, and returns the paths without extensions
+pub fn export_current_level(ecs : &World) -> Result<String, String> {
+    let seed = ecs.fetch::<rng::RunSeed>().seed;
+    let depth = ecs.fetch::<Map>().depth;
+    let turn = ecs.fetch::<super::TurnCounter>().turns;
+    let stem = Path::new(EXPORT_DIR).join(format!("seed{}-depth{}-turn{}", seed, depth, turn));
+    for format in ExportFormat::ALL {
+        export_map_to_file(ecs, &stem.with_extension(format.extension()), format, ExportOptions::default())?;
+    }
+    Ok(stem.display().to_string())
+}
+
+/// `export-map [--seed <n>] [--depth <n>] [--format text|ansi|png] [--out <file>]
+/// [--no-entities] [--revealed-only] [--no-blood]`
+/// - Builds the run's levels down to the depth without a window and writes
+///   that level out; with no `--out` text and ANSI go to standard output
+pub fn run_export_command(args : &[String]) -> Result<(), String> {
+    let mut seed = rng::random_seed();
+    let mut depth = 1;
+    let mut format = ExportFormat::Text;
+    let mut out : Option<String> = None;
+    let mut options = ExportOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag : &str| args.next().cloned().ok_or_else(|| format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--seed" => seed = value("--seed")?.parse().map_err(|_| "Seeds are whole numbers".to_string())?,
+            "--depth" => depth = value("--depth")?.parse().map_err(|_| "Depths are whole numbers".to_string())?,
+            "--format" => {
+                let name = value("--format")?;
+                format = ExportFormat::from_name(&name).ok_or_else(|| format!("\"{}\" isn't a format: use text, ansi or png", name))?;
+            }
+            "--out" => out = Some(value("--out")?),
+            "--no-entities" => options.entities = false,
+            "--revealed-only" => options.revealed_only = true,
+            "--no-blood" => options.bloodstains = false,
+            _ => return Err(format!("Unknown option \"{}\"", arg)),
+        }
+    }
+    if depth < 1 { return Err("Depths start at 1".to_string()); }
+
+    let mut sim = Simulation::new(seed)?;
+    for _ in 1..depth { goto_next_level(&mut sim.ecs); }
+    run_systems(&mut sim.ecs);
+
+    match out {
+        Some(path) => export_map_to_file(&sim.ecs, Path::new(&path), format, options),
+        None if format == ExportFormat::Png => Err("PNG exports need --out".to_string()),
+        None => {
+            let bytes = export_map(&sim.ecs, format, options)?;
+            std::io::stdout().write_all(&bytes).map_err(|e| format!("Unable to write the map out: {}", e))
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use rltk::BaseMap;
+use std::collections::VecDeque;
+use std::time::Instant;
+use super::{ Map, TileType, map_builders, rng, raws::{ self, RAWS, RawKind } };
+
+/// What one generated level measured
+#[derive(Clone, Debug)]
+pub struct LevelStats {
+    pub millis : f64,
+    pub floor_percent : f32,
+    /// Separate walkable areas; anything above 1 means part of the map can't
+    /// be reached
+    pub regions : usize,
+    /// Walking distance from the start to the down stairs, if both exist and
+    /// are joined up
+    pub stairs_distance : Option<f32>,
+    pub mobs : usize,
+    pub items : usize,
+    pub props : usize,
+}
+
+impl LevelStats {
+    /// Whether the level can be played through: one region, with a path from
+    /// the start to the stairs
+    pub fn connected(&self) -> bool { self.regions == 1 && self.stairs_distance.is_some() }
+}
+
+/// The builders the command can run: `None` is whatever the game builds at
+/// the depth, `Some(chain)` is `RANDOM_CHAINS[chain]`
+fn builder_name(chain : Option<usize>) -> &'static str {
+    match chain {
+        None => "Level builder",
+        Some(chain) => map_builders::RANDOM_CHAINS[chain],
+    }
+}
+
+/// Builds one level the way the game would for `seed` and measures it
+/// - Needs the raws loaded to tell mobs, items and props apart
+pub fn measure(chain : Option<usize>, depth : i32, seed : u64) -> LevelStats {
+    let started = Instant::now();
+    let mut rng = rng::map_rng(seed, depth);
+    let (width, height) = map_builders::level_size(depth);
+    let mut builder = match chain {
+        None => map_builders::level_builder(depth, &mut rng, width, height, false),
+        Some(chain) => map_builders::chain_builder(chain, depth, &mut rng, width, height, false),
+    };
+    builder.build_map(&mut rng);
+    let millis = started.elapsed().as_secs_f64() * 1000.0;
+
+    let build_data = &mut builder.build_data;
+    let map = &mut build_data.map;
+    let floor = map.tiles.iter().filter(|t| t.walkable()).count();
+    let floor_percent = floor as f32 * 100.0 / map.tiles.len() as f32;
+    let regions = count_regions(map);
+
+    let stairs = map.tiles.iter().position(|t| *t == TileType::DownStairs);
+    let stairs_distance = match (&build_data.starting_position, stairs) {
+        (Some(start), Some(stairs)) => {
+            let start_idx = map.xy_idx(start.x, start.y);
+            let distance = map_builders::distances_from(map, start_idx)[stairs];
+            if distance == f32::MAX { None } else { Some(distance) }
+        }
+        _ => None,
+    };
+
+    let (mut mobs, mut items, mut props) = (0, 0, 0);
+    let raws = RAWS.lock().unwrap();
+    for (_idx, name) in build_data.spawn_list.iter() {
+        match raws.kind_of(name) {
+            Some(RawKind::Mob) => mobs += 1,
+            Some(RawKind::Item) => items += 1,
+            Some(RawKind::Prop) => props += 1,
+            None => {}
+        }
+    }
+
+    LevelStats{ millis, floor_percent, regions, stairs_distance, mobs, items, props }
+}
+
+/// Flood fills the walkable tiles, moving the way the player can
+fn count_regions(map : &mut Map) -> usize {
+    map.populate_blocked();
+    let mut seen = vec![false; map.tiles.len()];
+    let mut regions = 0;
+    for idx in 0..map.tiles.len() {
+        if seen[idx] || !map.tiles[idx].walkable() { continue; }
+        regions += 1;
+        seen[idx] = true;
+        let mut open = VecDeque::from([idx]);
+        while let Some(current) = open.pop_front() {
+            for (next, _cost) in map.get_available_exits(current) {
+                if !seen[next] {
+                    seen[next] = true;
+                    open.push_back(next);
+                }
+            }
+        }
+    }
+    regions
+}
+
+/// Every run of one builder, summarised for the report
+pub struct BuilderReport {
+    pub name : &'static str,
+    pub runs : Vec<LevelStats>,
+}
+
+impl BuilderReport {
+    fn mean(&self, value : impl Fn(&LevelStats) -> f64) -> f64 {
+        self.runs.iter().map(value).sum::<f64>() / self.runs.len() as f64
+    }
+
+    /// Runs that left part of the map cut off or the stairs out of reach
+    pub fn failures(&self) -> usize { self.runs.iter().filter(|r| !r.connected()).count() }
+
+    fn max_millis(&self) -> f64 { self.runs.iter().map(|r| r.millis).fold(0.0, f64::max) }
+
+    fn mean_distance(&self) -> Option<f64> {
+        let distances : Vec<f64> = self.runs.iter().filter_map(|r| r.stairs_distance).map(|d| d as f64).collect();
+        if distances.is_empty() { None } else { Some(distances.iter().sum::<f64>() / distances.len() as f64) }
+    }
+
+    fn columns(&self) -> Vec<String> {
+        vec![
+            self.name.to_string(),
+            format!("{:.1}", self.mean(|r| r.millis)),
+            format!("{:.1}", self.max_millis()),
+            format!("{:.1}", self.mean(|r| r.floor_percent as f64)),
+            format!("{:.2}", self.mean(|r| r.regions as f64)),
+            self.mean_distance().map_or("-".to_string(), |d| format!("{:.1}", d)),
+            format!("{:.1}", self.mean(|r| r.mobs as f64)),
+            format!("{:.1}", self.mean(|r| r.items as f64)),
+            format!("{:.1}", self.mean(|r| r.props as f64)),
+            format!("{}", self.failures()),
+        ]
+    }
+}
+
+const HEADINGS : [&str; 10] = ["Builder", "ms", "max ms", "floor %", "regions", "to stairs", "mobs", "items", "props", "failed"];
+
+/// Runs the builder `runs` times, on consecutive seeds from `first_seed`
+pub fn report(chain : Option<usize>, depth : i32, first_seed : u64, runs : u64) -> BuilderReport {
+    BuilderReport{
+        name : builder_name(chain),
+        runs : (first_seed..first_seed + runs).map(|seed| measure(chain, depth, seed)).collect(),
+    }
+}
+
+fn print_table(reports : &[BuilderReport], csv : bool) {
+    let rows : Vec<Vec<String>> = reports.iter().map(|r| r.columns()).collect();
+    if csv {
+        println!("{}", HEADINGS.join(","));
+        for row in rows.iter() { println!("{}", row.join(",")); }
+        return;
+    }
+
+    let widths : Vec<usize> = (0..HEADINGS.len())
+        .map(|col| rows.iter().map(|row| row[col].len()).chain([HEADINGS[col].len()]).max().unwrap_or(0))
+        .collect();
+    let line = |cells : Vec<String>| -> String {
+        cells.iter().enumerate()
+            .map(|(col, cell)| if col == 0 { format!("{:<w$}", cell, w = widths[col]) } else { format!("{:>w$}", cell, w = widths[col]) })
+            .collect::<Vec<String>>()
+            .join("  ")
+    };
+    println!("{}", line(HEADINGS.iter().map(|h| h.to_string()).collect()));
+    for row in rows { println!("{}", line(row)); }
+}
+
+/// `mapgen-stats [--builder <name>] [--depth <n>] [--seed <n>] [--runs <n>] [--csv]`
+/// - Without `--builder` every builder is run, the game's own pick first
+/// - Fails if any run left a map disconnected, so it can gate a build
+pub fn run_stats_command(args : &[String]) -> Result<(), String> {
+    let mut chains : Vec<Option<usize>> = std::iter::once(None).chain((0..map_builders::RANDOM_CHAINS.len()).map(Some)).collect();
+    let mut depth = 2;
+    let mut first_seed = 1;
+    let mut runs = 20;
+    let mut csv = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag : &str| args.next().cloned().ok_or_else(|| format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--builder" => {
+                let name = value("--builder")?;
+                let chain = chains.iter().find(|c| builder_name(**c).eq_ignore_ascii_case(&name))
+                    .ok_or_else(|| format!("\"{}\" isn't a builder; try one of: {}", name, chains.iter().map(|c| builder_name(*c)).collect::<Vec<_>>().join(", ")))?;
+                chains = vec![*chain];
+            }
+            "--depth" => depth = value("--depth")?.parse().map_err(|_| "Depths are whole numbers".to_string())?,
+            "--seed" => first_seed = value("--seed")?.parse().map_err(|_| "Seeds are whole numbers".to_string())?,
+            "--runs" => runs = value("--runs")?.parse().map_err(|_| "Runs are whole numbers".to_string())?,
+            "--csv" => csv = true,
+            _ => return Err(format!("Unknown option \"{}\"", arg)),
+        }
+    }
+    if depth < 1 { return Err("Depths start at 1".to_string()); }
+    if runs < 1 { return Err("There has to be at least one run".to_string()); }
+
+    raws::load_raws()?;
+    let reports : Vec<BuilderReport> = chains.iter().map(|chain| report(*chain, depth, first_seed, runs)).collect();
+    print_table(&reports, csv);
+
+    let failed : Vec<String> = reports.iter().filter(|r| r.failures() > 0).map(|r| format!("{} ({})", r.name, r.failures())).collect();
+    if !failed.is_empty() {
+        return Err(format!("Disconnected maps from: {}", failed.join(", ")));
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use super::{ Map, map_builders, rng };
+
+//?  How long each snapshot stays up while playing
+const FRAME_MS : f32 = 300.0;
+
+/// The map generator debug screen: which chain to run, on what depth and
+/// seed, and where playback of its snapshots has got to
+pub struct MapgenVisualizer {
+    /// 0 runs whatever the game would build at `depth`; anything above that
+    /// is `RANDOM_CHAINS[chain - 1]`
+    pub chain : usize,
+    pub depth : i32,
+    pub seed : u64,
+    /// Whether a seed is being typed over the one the map was last built with
+    pub seed_typed : bool,
+    pub history : Vec<Map>,
+    pub index : usize,
+    pub paused : bool,
+    timer : f32,
+}
+
+impl MapgenVisualizer {
+    pub fn new(seed : u64) -> MapgenVisualizer {
+        let mut visualizer = MapgenVisualizer{
+            chain : 0, depth : 1, seed, seed_typed : false, history : Vec::new(), index : 0, paused : false, timer : 0.0,
+        };
+        visualizer.regenerate();
+        visualizer
+    }
+
+    /// How many builders there are to pick between
+    pub fn chain_count() -> usize { map_builders::RANDOM_CHAINS.len() + 1 }
+
+    pub fn builder_name(&self) -> &'static str {
+        if self.chain == 0 { "Level builder" } else { map_builders::RANDOM_CHAINS[self.chain - 1] }
+    }
+
+    /// Builds the map again from the current settings and plays it from the
+    /// first snapshot
+    /// - Uses the same RNG the game does, so a run's seed and depth show
+    ///   exactly how its level was made
+    pub fn regenerate(&mut self) {
+        let mut rng = rng::map_rng(self.seed, self.depth);
+        let (width, height) = map_builders::level_size(self.depth);
+        let mut builder = if self.chain == 0 {
+            map_builders::level_builder(self.depth, &mut rng, width, height, true)
+        } else {
+            map_builders::chain_builder(self.chain - 1, self.depth, &mut rng, width, height, true)
+        };
+        builder.build_map(&mut rng);
+
+        //?  The finished map is always the last frame, even for builders that snapshot nothing
+        let mut finished = builder.build_data.map.clone();
+        for v in finished.revealed_tiles.iter_mut() { *v = true; }
+        self.history = builder.build_data.history.clone();
+        self.history.push(finished);
+        self.index = 0;
+        self.timer = 0.0;
+        self.paused = false;
+        self.seed_typed = false;
+    }
+
+    pub fn select_chain(&mut self, delta : i32) {
+        let count = MapgenVisualizer::chain_count() as i32;
+        self.chain = (self.chain as i32 + delta).rem_euclid(count) as usize;
+        self.regenerate();
+    }
+
+    pub fn select_depth(&mut self, delta : i32) {
+        self.depth = i32::max(1, self.depth + delta);
+        self.regenerate();
+    }
+
+    /// Moves playback by `delta` snapshots and pauses it there
+    pub fn step(&mut self, delta : i32) {
+        let last = self.history.len() as i32 - 1;
+        self.index = (self.index as i32 + delta).clamp(0, last) as usize;
+        self.paused = true;
+    }
+
+    /// Advances playback by `frame_ms` of wall-clock time; it stops on the
+    /// finished map
+    pub fn update(&mut self, frame_ms : f32) {
+        if self.paused { return; }
+        self.timer += frame_ms;
+        if self.timer > FRAME_MS {
+            self.timer = 0.0;
+            if self.index + 1 < self.history.len() { self.index += 1; }
+        }
+    }
+
+    pub fn snapshot(&self) -> &Map { &self.history[self.index] }
+
+    /// Share of the current snapshot that can be walked on, in percent
+    pub fn floor_percent(&self) -> f32 {
+        let map = self.snapshot();
+        let floor = map.tiles.iter().filter(|t| t.walkable()).count();
+        floor as f32 * 100.0 / map.tiles.len() as f32
+    }
+}
@@ -1,12 +1,110 @@
-use rltk::{ Rltk, Point };
+use rltk::{ Rltk, Point, RandomNumberGenerator };
 use specs::prelude::*;
 use std::cmp::{ max, min };
 use super::{
     Map, TileType, Position, State, RunState, GameLog, Player, Monster,
     Viewshed, CombatStats, DoesMelee, Item, WantsToPickupItem, EntityMoved,
-    ThirstClock, ThirstState,
+    ThirstClock, ThirstState, WantsToUseItem, WantsToDropItem, WantsToRemoveItem,
+    Initiative, MyTurn, StatusEffects, StatusKind, Door, BlocksTile, BlocksVisibility, Renderable,
+    Bystander, Vendor, Name,
+    keybindings::{ Keybindings, Action },
+    map_export,
+    systems::initiative_system::{
+        spend_energy, MOVE_COST, ATTACK_COST, USE_ITEM_COST, HANDLE_ITEM_COST, WAIT_COST,
+    },
 };
 
+/// How many turns the player has taken this run
+pub struct TurnCounter { pub turns : i32 }
+
+/// Everything the player can spend a turn on, whether it came from the
+/// keyboard, a menu, or a scripted headless run
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum PlayerCommand {
+    Move { dx : i32, dy : i32 },
+    PickUp,
+    Descend,
+    Ascend,
+    Wait,
+    UseItem { item : Entity, target : Option<Point> },
+    DropItem { item : Entity },
+    RemoveItem { item : Entity },
+}
+
+impl PlayerCommand {
+    /// Carries out the command, pays its energy cost and returns the state
+    /// the game moves on to
+    pub fn apply(self, ecs : &mut World) -> RunState {
+        let mut newrunstate = RunState::PlayerTurn;
+        let cost = match self {
+            PlayerCommand::Move{ dx, dy } => {
+                let (dx, dy) = confused_direction(ecs).unwrap_or((dx, dy));
+                try_move_player(dx, dy, ecs);
+                let attacking = ecs.read_storage::<DoesMelee>().contains(*ecs.fetch::<Entity>());
+                if attacking { ATTACK_COST } else { MOVE_COST }
+            }
+            PlayerCommand::PickUp => {
+                get_item(ecs);
+                HANDLE_ITEM_COST
+            }
+            PlayerCommand::Descend => {
+                if try_next_level(ecs) { newrunstate = RunState::NextLevel; }
+                MOVE_COST
+            }
+            PlayerCommand::Ascend => {
+                if try_previous_level(ecs) { newrunstate = RunState::PreviousLevel; }
+                MOVE_COST
+            }
+            PlayerCommand::Wait => {
+                skip_turn(ecs);
+                WAIT_COST
+            }
+            PlayerCommand::UseItem{ item, target } => {
+                let mut intent = ecs.write_storage::<WantsToUseItem>();
+                intent.insert(*ecs.fetch::<Entity>(), WantsToUseItem{ item, target })
+                    .expect("Unable to intentionalize");
+                USE_ITEM_COST
+            }
+            PlayerCommand::DropItem{ item } => {
+                let mut intent = ecs.write_storage::<WantsToDropItem>();
+                intent.insert(*ecs.fetch::<Entity>(), WantsToDropItem{ item })
+                    .expect("Unable to insert intent");
+                HANDLE_ITEM_COST
+            }
+            PlayerCommand::RemoveItem{ item } => {
+                let mut intent = ecs.write_storage::<WantsToRemoveItem>();
+                intent.insert(*ecs.fetch::<Entity>(), WantsToRemoveItem{ item })
+                    .expect("Unable to intentionalize removing item");
+                HANDLE_ITEM_COST
+            }
+        };
+
+        let player_entity = *ecs.fetch::<Entity>();
+        spend_energy(&mut ecs.write_storage::<Initiative>(), &mut ecs.write_storage::<MyTurn>(), player_entity, cost);
+        let turns = {
+            let mut counter = ecs.write_resource::<TurnCounter>();
+            counter.turns += 1;
+            counter.turns
+        };
+        ecs.write_resource::<GameLog>().turn = turns;
+
+        newrunstate
+    }
+}
+
+/// A confused player staggers in a random direction instead of the one chosen
+fn confused_direction(ecs : &mut World) -> Option<(i32, i32)> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let confused = ecs.read_storage::<StatusEffects>().get(player_entity).is_some_and(|s| s.has(StatusKind::Confusion));
+    if !confused { return None; }
+
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let directions = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+    let direction = directions[(rng.roll_dice(1, 8) - 1) as usize];
+    ecs.fetch_mut::<GameLog>().add("You stumble about in confusion.");
+    Some(direction)
+}
+
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.write_storage::<Player>();
@@ -14,23 +112,50 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut does_melee = ecs.write_storage::<DoesMelee>();
     let combat_stats = ecs.read_storage::<CombatStats>();
     let mut entity_moved = ecs.write_storage::<EntityMoved>();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut blocks_tile = ecs.write_storage::<BlocksTile>();
+    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let bystanders = ecs.read_storage::<Bystander>();
+    let vendors = ecs.read_storage::<Vendor>();
+    let names = ecs.read_storage::<Name>();
     let map = ecs.fetch::<Map>();
     let entities = ecs.entities();
+    let mut swap_entities : Vec<(Entity, i32, i32)> = Vec::new();
 
     for (entity, _player, pos, viewshed) in (&entities, &players, &mut positions, &mut viewsheds).join() {
         if pos.x + delta_x < 1 || pos.x + delta_x > map.width-1 || pos.y + delta_y < 1 || pos.y + delta_y > map.height - 1 { return; }
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
+        let mut swapping = false;
         for potential_target in map.tile_content[destination_idx].iter() {
+            //?  Townsfolk aren't fought: bystanders trade places, vendors hold theirs
+            if bystanders.get(*potential_target).is_some() {
+                swap_entities.push((*potential_target, pos.x, pos.y));
+                swapping = true;
+                continue;
+            }
+            if vendors.get(*potential_target).is_some() {
+                if let Some(name) = names.get(*potential_target) {
+                    ecs.fetch_mut::<GameLog>().add(format!("The {} is busy minding the shop.", name.name));
+                }
+                return;
+            }
             let target = combat_stats.get(*potential_target);
             if let Some(_target) = target {
                 does_melee.insert(entity, DoesMelee{ target: *potential_target }).expect("Add target failed");
             }
+            //?  Bumping a closed door opens it; stepping through takes another move
+            if Door::open_door(&mut doors, &mut blocks_tile, &mut blocks_visibility, &mut renderables, *potential_target) {
+                viewshed.dirty = true;
+                ecs.fetch_mut::<GameLog>().add("You open the door.");
+                return;
+            }
         }
 
-        if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+        if !map.blocked[destination_idx] || swapping {
+            pos.x = min(map.width-1, max(0, pos.x + delta_x));
+            pos.y = min(map.height-1, max(0, pos.y + delta_y));
             entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert moved marker.");
 
             viewshed.dirty = true;
@@ -40,6 +165,14 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             ppos.y = pos.y;
         }
     }
+
+    for (bystander, x, y) in swap_entities {
+        if let Some(their_pos) = positions.get_mut(bystander) {
+            their_pos.x = x;
+            their_pos.y = y;
+            entity_moved.insert(bystander, EntityMoved{}).expect("Unable to insert moved marker.");
+        }
+    }
 }
 
 fn get_item(ecs: &mut World) {
@@ -58,7 +191,7 @@ fn get_item(ecs: &mut World) {
     }
 
     match target_item {
-        None => gamelog.entries.push("There is nothing here to pick up.".to_string()),
+        None => gamelog.add("There is nothing here to pick up."),
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickupItem>();
             pickup.insert(*player_entity, WantsToPickupItem{ collected_by: *player_entity, item }).expect("Unable to insert picks up item");
@@ -74,12 +207,25 @@ pub fn try_next_level(ecs: &mut World) -> bool {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
-        gamelog.entries.push("There is no way down from here.".to_string());
+        gamelog.add("There is no way down from here.");
+        false
+    }
+}
+
+pub fn try_previous_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    if map.tiles[player_idx] == TileType::UpStairs {
+        true
+    } else {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.add("There is no way up from here.");
         false
     }
 }
 
-fn skip_turn(ecs: &mut World) -> RunState {
+fn skip_turn(ecs: &mut World) {
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let monsters = ecs.read_storage::<Monster>();
@@ -117,61 +263,54 @@ fn skip_turn(ecs: &mut World) -> RunState {
         let player_hp = health_components.get_mut(*player_entity).unwrap();
         player_hp.hp = i32::min(player_hp.hp + 1, player_hp.max_hp);
         if player_hp.hp >= player_hp.max_hp {
-            log.entries.push("You wait".to_string());
+            log.add("You wait");
         } else {
-            log.entries.push("You rest a moment to catch your breath.".to_string());
+            log.add("You rest a moment to catch your breath.");
         }
     } else if unseen {
-        log.entries.push("Your thirst prevents rest.".to_string());
+        log.add("Your thirst prevents rest.");
     } else {
-        log.entries.push("You wait. There is an enemy nearby.".to_string());
+        log.add("You wait. There is an enemy nearby.");
     }
-
-    RunState::PlayerTurn
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
-    use rltk::VirtualKeyCode::*;
-    match ctx.key {
+    let action = match ctx.key {
         None => { return RunState::AwaitingInput }
-        Some(key) => match key {
-            Left | A => try_move_player(-1, 0, &mut gs.ecs),
-
-            Right | F => try_move_player(1, 0, &mut gs.ecs),
-
-            Up | D => try_move_player(0, -1, &mut gs.ecs),
-
-            Down | S => try_move_player(0, 1, &mut gs.ecs),
-
-            E => try_move_player(1, -1, &mut gs.ecs), //? NE
-
-            W => try_move_player(-1, -1, &mut gs.ecs), //? NW
-
-            C => try_move_player(1, 1, &mut gs.ecs), //? SE
-
-            X => try_move_player(-1, 1, &mut gs.ecs), //? SW
-
-            G => get_item(&mut gs.ecs),
-
-            Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
-                }
-            }
-
-            I => return RunState::ShowInventory,
-
-            T => return RunState::ShowDropItem,
-
-            R => return RunState::ShowRemoveItem,
-
-            Escape => return RunState::SaveGame,
-
-            Space => return skip_turn(&mut gs.ecs),
-
-            _ => { return RunState::AwaitingInput }
+        Some(key) => match gs.ecs.fetch::<Keybindings>().action_for(key) {
+            None => { return RunState::AwaitingInput }
+            Some(action) => action,
         },
-    }
+    };
+
+    let command = match action {
+        Action::MoveNorth => PlayerCommand::Move{ dx: 0, dy: -1 },
+        Action::MoveSouth => PlayerCommand::Move{ dx: 0, dy: 1 },
+        Action::MoveWest => PlayerCommand::Move{ dx: -1, dy: 0 },
+        Action::MoveEast => PlayerCommand::Move{ dx: 1, dy: 0 },
+        Action::MoveNorthWest => PlayerCommand::Move{ dx: -1, dy: -1 },
+        Action::MoveNorthEast => PlayerCommand::Move{ dx: 1, dy: -1 },
+        Action::MoveSouthWest => PlayerCommand::Move{ dx: -1, dy: 1 },
+        Action::MoveSouthEast => PlayerCommand::Move{ dx: 1, dy: 1 },
+        Action::Wait => PlayerCommand::Wait,
+        Action::PickUp => PlayerCommand::PickUp,
+        Action::Descend => PlayerCommand::Descend,
+        Action::Ascend => PlayerCommand::Ascend,
+        Action::Inventory => return RunState::ShowInventory,
+        Action::Drop => return RunState::ShowDropItem,
+        Action::Remove => return RunState::ShowRemoveItem,
+        Action::Log => return RunState::ShowLog{ offset: 0 },
+        Action::Help => return RunState::ShowHelp,
+        Action::SaveAndQuit => return RunState::SaveGame,
+        Action::ExportMap => {
+            let message = match map_export::export_current_level(&gs.ecs) {
+                Ok(stem) => format!("Map exported to {}.", stem),
+                Err(e) => format!("Unable to export the map: {}", e),
+            };
+            gs.ecs.fetch_mut::<GameLog>().add(message);
+            return RunState::AwaitingInput;
+        }
+    };
 
-    RunState::PlayerTurn
+    command.apply(&mut gs.ecs)
 }
@@ -0,0 +1,71 @@
+use serde::{ Deserialize };
+use super::super::{ EquipmentSlot, StatusKind, Rarity };
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Item {
+    pub name : String,
+    pub renderable : Option<Renderable>,
+    #[serde(default)]
+    pub rarity : Rarity,
+    #[serde(default)]
+    pub consumable : bool,
+    pub provides_healing : Option<ProvidesHealing>,
+    #[serde(default)]
+    pub provides_water : bool,
+    #[serde(default)]
+    pub magic_mapper : bool,
+    pub ranged : Option<Ranged>,
+    pub inflicts_damage : Option<InflictsDamage>,
+    pub area_of_effect : Option<AreaOfEffect>,
+    pub status_effect : Option<StatusEffect>,
+    pub equippable : Option<Equippable>,
+    pub melee_power_bonus : Option<MeleePowerBonus>,
+    pub defense_bonus : Option<DefenseBonus>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Renderable {
+    pub glyph : String,
+    pub fg : String,
+    pub bg : String,
+    pub render_order : i32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ProvidesHealing { pub heal_amount : i32 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Ranged { pub range : i32 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct InflictsDamage { pub damage : i32 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AreaOfEffect { pub radius : i32 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct StatusEffect {
+    pub effect : StatusKind,
+    pub turns : i32,
+    #[serde(default)]
+    pub magnitude : i32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Equippable { pub slot : EquipmentSlot }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MeleePowerBonus { pub power : i32 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DefenseBonus { pub defense : i32 }
@@ -0,0 +1,45 @@
+use serde::{ Deserialize };
+use super::Renderable;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Mob {
+    pub name : String,
+    pub renderable : Option<Renderable>,
+    #[serde(default)]
+    pub blocks_tile : bool,
+    #[serde(default)]
+    pub opens_doors : bool,
+    #[serde(default)]
+    pub ai : MobAi,
+    pub viewshed : Viewshed,
+    pub combat_stats : CombatStats,
+    pub speed : Option<i32>,
+    pub level : Option<i32>,
+}
+
+/// How a mob behaves towards the player
+#[derive(Deserialize, Debug, Default, PartialEq, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum MobAi {
+    /// Hunts the player down and attacks
+    #[default]
+    Melee,
+    /// Wanders about harmlessly
+    Bystander,
+    /// Stays at its post
+    Vendor,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Viewshed { pub range : i32 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CombatStats {
+    pub max_hp : i32,
+    pub hp : i32,
+    pub defense : i32,
+    pub power : i32,
+}
@@ -0,0 +1,38 @@
+use serde::{ Deserialize };
+use std::sync::{ LazyLock, Mutex };
+mod item_structs;
+pub use item_structs::*;
+mod mob_structs;
+pub use mob_structs::*;
+mod prop_structs;
+pub use prop_structs::*;
+mod spawn_table_structs;
+pub use spawn_table_structs::*;
+mod rawmaster;
+pub use rawmaster::*;
+
+/// Where the game looks for its entity definitions. Read at startup, so
+/// editing this file only needs a restart, not a rebuild.
+pub const RAWS_PATH : &str = "./raws/spawns.json";
+
+pub static RAWS : LazyLock<Mutex<RawMaster>> = LazyLock::new(|| Mutex::new(RawMaster::empty()));
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Raws {
+    pub items : Vec<Item>,
+    pub mobs : Vec<Mob>,
+    pub props : Vec<Prop>,
+    pub spawn_table : Vec<SpawnTableEntry>,
+}
+
+/// Reads and validates the raws file into `RAWS`
+/// - Errors name the file and, for JSON problems, the line and column
+pub fn load_raws() -> Result<(), String> {
+    let raw_string = std::fs::read_to_string(RAWS_PATH)
+        .map_err(|e| format!("Unable to read {}: {}", RAWS_PATH, e))?;
+    let decoder : Raws = serde_json::from_str(&raw_string)
+        .map_err(|e| format!("Unable to parse {}: {}", RAWS_PATH, e))?;
+    RAWS.lock().unwrap().load(decoder)
+        .map_err(|e| format!("Invalid entry in {}: {}", RAWS_PATH, e))
+}
@@ -0,0 +1,23 @@
+use serde::{ Deserialize };
+use super::{ Renderable, InflictsDamage, StatusEffect };
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Prop {
+    pub name : String,
+    pub renderable : Option<Renderable>,
+    #[serde(default)]
+    pub hidden : bool,
+    #[serde(default)]
+    pub blocks_tile : bool,
+    #[serde(default)]
+    pub blocks_visibility : bool,
+    #[serde(default)]
+    pub door : bool,
+    #[serde(default)]
+    pub entry_trigger : bool,
+    #[serde(default)]
+    pub triggers_once : bool,
+    pub inflicts_damage : Option<InflictsDamage>,
+    pub status_effect : Option<StatusEffect>,
+}
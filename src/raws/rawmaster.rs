@@ -0,0 +1,240 @@
+use rltk::RGB;
+use specs::prelude::*;
+use specs::saveload::{ MarkedBuilder, SimpleMarker };
+use std::collections::HashMap;
+use super::{ Raws, MobAi };
+use super::super::{
+    components::*, random_table::RandomTable, systems::initiative_system::NORMAL_SPEED,
+};
+
+pub enum SpawnType {
+    AtPosition { x : i32, y : i32 },
+}
+
+/// Which section of the raws an entity is defined in
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum RawKind { Item, Mob, Prop }
+
+pub struct RawMaster {
+    raws : Raws,
+    item_index : HashMap<String, usize>,
+    mob_index : HashMap<String, usize>,
+    prop_index : HashMap<String, usize>,
+}
+
+impl RawMaster {
+    pub fn empty() -> RawMaster {
+        RawMaster {
+            raws : Raws{ items: Vec::new(), mobs: Vec::new(), props: Vec::new(), spawn_table: Vec::new() },
+            item_index : HashMap::new(),
+            mob_index : HashMap::new(),
+            prop_index : HashMap::new(),
+        }
+    }
+
+    /// Indexes the raws by name, rejecting duplicates, bad renderables and
+    /// spawn table entries that don't name a known entity
+    pub fn load(&mut self, raws : Raws) -> Result<(), String> {
+        self.raws = raws;
+        self.item_index = HashMap::new();
+        self.mob_index = HashMap::new();
+        self.prop_index = HashMap::new();
+        let mut used_names : Vec<String> = Vec::new();
+
+        for (i, item) in self.raws.items.iter().enumerate() {
+            check_name(&item.name, &mut used_names)?;
+            check_renderable(&item.name, &item.renderable)?;
+            self.item_index.insert(item.name.clone(), i);
+        }
+        for (i, mob) in self.raws.mobs.iter().enumerate() {
+            check_name(&mob.name, &mut used_names)?;
+            check_renderable(&mob.name, &mob.renderable)?;
+            self.mob_index.insert(mob.name.clone(), i);
+        }
+        for (i, prop) in self.raws.props.iter().enumerate() {
+            check_name(&prop.name, &mut used_names)?;
+            check_renderable(&prop.name, &prop.renderable)?;
+            self.prop_index.insert(prop.name.clone(), i);
+        }
+
+        for spawn in self.raws.spawn_table.iter() {
+            if !used_names.contains(&spawn.name) {
+                return Err(format!("spawn_table names {}, which is not an item, mob or prop", spawn.name));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn kind_of(&self, key : &str) -> Option<RawKind> {
+        if self.item_index.contains_key(key) { return Some(RawKind::Item); }
+        if self.mob_index.contains_key(key) { return Some(RawKind::Mob); }
+        if self.prop_index.contains_key(key) { return Some(RawKind::Prop); }
+        None
+    }
+}
+
+fn check_name(name : &str, used_names : &mut Vec<String>) -> Result<(), String> {
+    if used_names.iter().any(|n| n == name) {
+        return Err(format!("{} is defined more than once", name));
+    }
+    used_names.push(name.to_string());
+    Ok(())
+}
+
+fn check_renderable(name : &str, renderable : &Option<super::Renderable>) -> Result<(), String> {
+    if let Some(renderable) = renderable {
+        if renderable.glyph.chars().count() != 1 {
+            return Err(format!("{} has glyph \"{}\"; glyphs must be a single character", name, renderable.glyph));
+        }
+        for color in [&renderable.fg, &renderable.bg] {
+            if RGB::from_hex(color).is_err() {
+                return Err(format!("{} has color \"{}\"; colors must be written as #RRGGBB", name, color));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn spawn_position(new_entity : EntityBuilder, pos : SpawnType) -> EntityBuilder {
+    match pos {
+        SpawnType::AtPosition{ x, y } => new_entity.with(Position{ x, y }),
+    }
+}
+
+fn get_renderable_component(renderable : &super::Renderable) -> Renderable {
+    Renderable {
+        glyph : rltk::to_cp437(renderable.glyph.chars().next().unwrap()),
+        fg : RGB::from_hex(&renderable.fg).expect("Invalid RGB"),
+        bg : RGB::from_hex(&renderable.bg).expect("Invalid RGB"),
+        render_order : renderable.render_order,
+    }
+}
+
+fn get_status_component(status : &super::StatusEffect) -> InflictsStatus {
+    InflictsStatus {
+        effect : StatusEffect{ kind: status.effect, turns: status.turns, magnitude: status.magnitude },
+    }
+}
+
+pub fn spawn_named_item(raws : &RawMaster, new_entity : EntityBuilder, key : &str, pos : SpawnType) -> Option<Entity> {
+    let item_template = &raws.raws.items[*raws.item_index.get(key)?];
+
+    let mut eb = spawn_position(new_entity, pos);
+    if let Some(renderable) = &item_template.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    eb = eb.with(Name{ name : item_template.name.clone() });
+    eb = eb.with(Item{ rarity : item_template.rarity });
+
+    if item_template.consumable { eb = eb.with(Consumable{}); }
+    if item_template.provides_water { eb = eb.with(ProvidesWater{}); }
+    if item_template.magic_mapper { eb = eb.with(MagicMapper{}); }
+    if let Some(healing) = &item_template.provides_healing {
+        eb = eb.with(ProvidesHealing{ heal_amount: healing.heal_amount });
+    }
+    if let Some(ranged) = &item_template.ranged {
+        eb = eb.with(Ranged{ range: ranged.range });
+    }
+    if let Some(damage) = &item_template.inflicts_damage {
+        eb = eb.with(InflictsDamage{ damage: damage.damage });
+    }
+    if let Some(aoe) = &item_template.area_of_effect {
+        eb = eb.with(AreaOfEffect{ radius: aoe.radius });
+    }
+    if let Some(status) = &item_template.status_effect {
+        eb = eb.with(get_status_component(status));
+    }
+    if let Some(equippable) = &item_template.equippable {
+        eb = eb.with(Equippable{ slot: equippable.slot });
+    }
+    if let Some(bonus) = &item_template.melee_power_bonus {
+        eb = eb.with(MeleePowerBonus{ power: bonus.power });
+    }
+    if let Some(bonus) = &item_template.defense_bonus {
+        eb = eb.with(DefenseBonus{ defense: bonus.defense });
+    }
+
+    Some(eb.marked::<SimpleMarker<SerializeMe>>().build())
+}
+
+pub fn spawn_named_mob(raws : &RawMaster, new_entity : EntityBuilder, key : &str, pos : SpawnType) -> Option<Entity> {
+    let mob_template = &raws.raws.mobs[*raws.mob_index.get(key)?];
+
+    let mut eb = spawn_position(new_entity, pos);
+    if let Some(renderable) = &mob_template.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    eb = eb.with(Name{ name : mob_template.name.clone() });
+    eb = match mob_template.ai {
+        MobAi::Melee => eb.with(Monster{}),
+        MobAi::Bystander => eb.with(Bystander{}),
+        MobAi::Vendor => eb.with(Vendor{}),
+    };
+    if mob_template.blocks_tile { eb = eb.with(BlocksTile{}); }
+    if mob_template.opens_doors { eb = eb.with(OpensDoors{}); }
+    eb = eb.with(Viewshed{ visible_tiles : Vec::new(), range: mob_template.viewshed.range, dirty: true });
+    eb = eb.with(CombatStats{
+        max_hp : mob_template.combat_stats.max_hp,
+        hp : mob_template.combat_stats.hp,
+        defense : mob_template.combat_stats.defense,
+        power : mob_template.combat_stats.power,
+    });
+    eb = eb.with(Initiative{ speed: mob_template.speed.unwrap_or(NORMAL_SPEED), energy: 0 });
+    eb = eb.with(Experience{ level: mob_template.level.unwrap_or(1), xp: 0 });
+
+    Some(eb.marked::<SimpleMarker<SerializeMe>>().build())
+}
+
+pub fn spawn_named_prop(raws : &RawMaster, new_entity : EntityBuilder, key : &str, pos : SpawnType) -> Option<Entity> {
+    let prop_template = &raws.raws.props[*raws.prop_index.get(key)?];
+
+    let mut eb = spawn_position(new_entity, pos);
+    if let Some(renderable) = &prop_template.renderable {
+        eb = eb.with(get_renderable_component(renderable));
+    }
+    eb = eb.with(Name{ name : prop_template.name.clone() });
+
+    if prop_template.hidden { eb = eb.with(Hidden{}); }
+    if prop_template.blocks_tile { eb = eb.with(BlocksTile{}); }
+    if prop_template.blocks_visibility { eb = eb.with(BlocksVisibility{}); }
+    if prop_template.door { eb = eb.with(Door{ open: false }); }
+    if prop_template.entry_trigger { eb = eb.with(EntryTrigger{}); }
+    if prop_template.triggers_once { eb = eb.with(TriggersOnce{}); }
+    if let Some(damage) = &prop_template.inflicts_damage {
+        eb = eb.with(InflictsDamage{ damage: damage.damage });
+    }
+    if let Some(status) = &prop_template.status_effect {
+        eb = eb.with(get_status_component(status));
+    }
+
+    Some(eb.marked::<SimpleMarker<SerializeMe>>().build())
+}
+
+/// Spawns whatever the raws call `key`, or returns `None` if nothing has that name
+pub fn spawn_named_entity(raws : &RawMaster, ecs : &mut World, key : &str, pos : SpawnType) -> Option<Entity> {
+    if raws.item_index.contains_key(key) {
+        return spawn_named_item(raws, ecs.create_entity(), key, pos);
+    } else if raws.mob_index.contains_key(key) {
+        return spawn_named_mob(raws, ecs.create_entity(), key, pos);
+    } else if raws.prop_index.contains_key(key) {
+        return spawn_named_prop(raws, ecs.create_entity(), key, pos);
+    }
+
+    None
+}
+
+pub fn get_spawn_table_for_depth(raws : &RawMaster, depth : i32) -> RandomTable {
+    let available_options = raws.raws.spawn_table
+        .iter()
+        .filter(|a| a.min_depth.is_none_or(|min| depth >= min) && a.max_depth.is_none_or(|max| depth <= max));
+
+    let mut rt = RandomTable::new();
+    for e in available_options {
+        let mut weight = e.weight;
+        if e.add_map_depth_to_weight { weight += depth; }
+        rt = rt.add(e.name.clone(), weight);
+    }
+
+    rt
+}
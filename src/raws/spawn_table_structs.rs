@@ -0,0 +1,12 @@
+use serde::{ Deserialize };
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnTableEntry {
+    pub name : String,
+    pub weight : i32,
+    pub min_depth : Option<i32>,
+    pub max_depth : Option<i32>,
+    #[serde(default)]
+    pub add_map_depth_to_weight : bool,
+}
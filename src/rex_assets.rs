@@ -1,7 +1,13 @@
 use rltk::{ rex::XpFile };
+use std::sync::Once;
 
 rltk::embedded_resource!(SMALL_DUNGEON, "../resources/SmallDungeon_80x50.xp");
 // rltk::embedded_resource!(SMALL_DUNGEON, "../resources/cubecircles.xp");
+rltk::embedded_resource!(GOBLIN_WARREN, "../resources/prefabs/goblin_warren.xp");
+rltk::embedded_resource!(TRAP_RING, "../resources/prefabs/trap_ring.xp");
+rltk::embedded_resource!(GOBLIN_CAMP, "../resources/prefabs/goblin_camp.xp");
+rltk::embedded_resource!(CHECKERBOARD, "../resources/prefabs/checkerboard.xp");
+rltk::embedded_resource!(ORC_CAMP, "../resources/prefabs/orc_camp.xp");
 
 pub struct RexAssets { pub menu : XpFile }
 impl RexAssets {
@@ -16,3 +22,16 @@ impl RexAssets {
         }
     }
 }
+
+/// Makes the map builders' prefab templates loadable with
+/// `XpFile::from_resource`; safe to call any number of times
+pub fn link_prefabs() {
+    static LINKED : Once = Once::new();
+    LINKED.call_once(|| {
+        rltk::link_resource!(GOBLIN_WARREN, "../resources/prefabs/goblin_warren.xp");
+        rltk::link_resource!(TRAP_RING, "../resources/prefabs/trap_ring.xp");
+        rltk::link_resource!(GOBLIN_CAMP, "../resources/prefabs/goblin_camp.xp");
+        rltk::link_resource!(CHECKERBOARD, "../resources/prefabs/checkerboard.xp");
+        rltk::link_resource!(ORC_CAMP, "../resources/prefabs/orc_camp.xp");
+    });
+}
@@ -0,0 +1,55 @@
+use rltk::RandomNumberGenerator;
+
+/// The seed the current run was started from. Every map is generated from
+/// this seed and its depth, and the gameplay RNG resource is seeded from it,
+/// so the same seed and the same inputs always give the same game.
+#[derive(Copy, Clone, PartialEq)]
+pub struct RunSeed { pub seed : u64 }
+
+/// Picks a fresh seed, kept short enough to read out of a bug report
+pub fn random_seed() -> u64 {
+    RandomNumberGenerator::new().next_u64() % 1_000_000_000
+}
+
+/// RNG used to build (and populate) the map for one depth of a run
+pub fn map_rng(seed : u64, depth : i32) -> RandomNumberGenerator {
+    RandomNumberGenerator::seeded(mix(seed, depth as u64))
+}
+
+/// RNG used for in-game rolls, stored as the `RandomNumberGenerator` resource
+/// - `depth` lets a loaded game pick up a stream that doesn't replay the rolls
+///   made on the levels above
+pub fn gameplay_rng(seed : u64, depth : i32) -> RandomNumberGenerator {
+    RandomNumberGenerator::seeded(mix(seed ^ 0x9E37_79B9_7F4A_7C15, depth as u64))
+}
+
+/// Reads `--seed <n>` or `--seed=<n>` from the command line
+pub fn seed_from_args() -> Option<u64> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = if arg == "--seed" {
+            args.next().unwrap_or_default()
+        } else if let Some(value) = arg.strip_prefix("--seed=") {
+            value.to_string()
+        } else {
+            continue;
+        };
+
+        match value.parse() {
+            Ok(seed) => return Some(seed),
+            Err(_) => {
+                eprintln!("Ignoring --seed \"{}\": seeds are whole numbers", value);
+                return None;
+            }
+        }
+    }
+    None
+}
+
+//?  splitmix64, so neighbouring depths don't get neighbouring seeds
+fn mix(seed : u64, salt : u64) -> u64 {
+    let mut z = seed.wrapping_add(salt.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -3,13 +3,16 @@
 //   this file visible to the the rest of the project where a mod reference to
 //   this file is included.
 
+pub mod bystander_ai_system;
 pub mod damage_system;
+pub mod initiative_system;
 pub mod inventory_system;
 pub mod map_indexing_system;
 pub mod melee_combat_system;
 pub mod monster_ai_system;
 pub mod particle_system;
 pub mod saveload_system;
+pub mod status_effect_system;
 pub mod thirst_system;
 pub mod trigger_system;
 pub mod visibility_system;
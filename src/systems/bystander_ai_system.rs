@@ -0,0 +1,74 @@
+use specs::prelude::*;
+use super::super::{
+    Viewshed, Bystander, Vendor, Map, Position, EntityMoved, Initiative, MyTurn,
+};
+use super::initiative_system::{ spend_energy, MOVE_COST, WAIT_COST };
+
+/// Turns for the townsfolk: bystanders amble about at random and vendors
+/// stay where they are
+pub struct BystanderAI {}
+impl<'a> System<'a> for BystanderAI {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( WriteExpect<'a, Map>,
+                        WriteExpect<'a, rltk::RandomNumberGenerator>,
+                        Entities<'a>,
+                        WriteStorage<'a, Viewshed>,
+                        ReadStorage<'a, Bystander>,
+                        ReadStorage<'a, Vendor>,
+                        WriteStorage<'a, Position>,
+                        WriteStorage<'a, EntityMoved>,
+                        WriteStorage<'a, Initiative>,
+                        WriteStorage<'a, MyTurn> );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (
+            mut map, mut rng,
+            entities,
+            mut viewsheds,
+            bystanders,
+            vendors,
+            mut positions,
+            mut entity_moved,
+            mut initiatives,
+            mut turns,
+        ) = data;
+
+        let mut finished : Vec<(Entity, i32)> = Vec::new();
+        for (entity, viewshed, pos, _turn) in (&entities, &mut viewsheds, &mut positions, &turns).join() {
+            if vendors.get(entity).is_some() {
+                finished.push((entity, WAIT_COST));
+                continue;
+            }
+            if bystanders.get(entity).is_none() { continue; }
+
+            let mut cost = WAIT_COST;
+            let (dx, dy) = match rng.roll_dice(1, 5) {
+                1 => (-1, 0),
+                2 => (1, 0),
+                3 => (0, -1),
+                4 => (0, 1),
+                _ => (0, 0),
+            };
+            let (x, y) = (pos.x + dx, pos.y + dy);
+            if (dx, dy) != (0, 0) && x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
+                let dest_idx = map.xy_idx(x, y);
+                if !map.blocked[dest_idx] {
+                    let idx = map.xy_idx(pos.x, pos.y);
+                    map.blocked[idx] = false;
+                    pos.x = x;
+                    pos.y = y;
+                    map.blocked[dest_idx] = true;
+                    entity_moved.insert(entity, EntityMoved{}).expect("Unable to insert moved marker.");
+                    viewshed.dirty = true;
+                    cost = MOVE_COST;
+                }
+            }
+
+            finished.push((entity, cost));
+        }
+
+        for (entity, cost) in finished {
+            spend_energy(&mut initiatives, &mut turns, entity, cost);
+        }
+    }
+}
@@ -1,29 +1,76 @@
 use::specs::prelude::*;
 use super::super::{
-    CombatStats, SufferDamage, Player, Name, GameLog, RunState, Map, Position
+    CombatStats, SufferDamage, Player, Name, GameLog, RunState, Map, Position,
+    Experience, systems::particle_system::ParticleBuilder,
 };
 
+/// XP the player needs to bank to go from `level` to the next
+pub fn xp_to_next_level(level : i32) -> i32 { level * 500 }
+
+/// XP the player earns for killing something of `level` on `depth`
+fn xp_for_kill(level : i32, depth : i32) -> i32 { level * 100 + (depth - 1) * 25 }
+
 pub struct DamageSystem {}
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = ( WriteStorage<'a, CombatStats>,
                         WriteStorage<'a, SufferDamage>,
                         ReadStorage<'a, Position>,
                         WriteExpect<'a, Map>,
-                        Entities<'a>    );
+                        Entities<'a>,
+                        ReadExpect<'a, Entity>,
+                        WriteStorage<'a, Experience>,
+                        WriteExpect<'a, GameLog>,
+                        WriteExpect<'a, ParticleBuilder>    );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (
+            mut stats, mut damage, positions, mut map, entities,
+            player_entity, mut experience, mut log, mut particle_builder,
+        ) = data;
 
+        let mut xp_gain = 0;
         for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+            let was_alive = stats.hp > 0;
+            stats.hp -= damage.amount.iter().map(|(amount, _from_player)| amount).sum::<i32>();
             let pos = positions.get(entity);
             if let Some(pos) = pos {
                 let idx = map.xy_idx(pos.x, pos.y);
                 map.bloodstains.insert(idx);
             }
+
+            let player_hit = damage.amount.iter().any(|(_amount, from_player)| *from_player);
+            if was_alive && stats.hp < 1 && player_hit && entity != *player_entity {
+                let level = experience.get(entity).map_or(1, |e| e.level);
+                xp_gain += xp_for_kill(level, map.depth);
+            }
         }
 
         damage.clear();
+
+        //?  Bank the XP and level up as many times as it pays for
+        if xp_gain == 0 { return; }
+        if let Some(player_xp) = experience.get_mut(*player_entity) {
+            player_xp.xp += xp_gain;
+            while player_xp.xp >= xp_to_next_level(player_xp.level) {
+                player_xp.xp -= xp_to_next_level(player_xp.level);
+                player_xp.level += 1;
+
+                if let Some(player_stats) = stats.get_mut(*player_entity) {
+                    player_stats.max_hp += 5;
+                    player_stats.power += 1;
+                    if player_xp.level % 2 == 0 { player_stats.defense += 1; }
+                    player_stats.hp = player_stats.max_hp;
+                }
+                log.entry().notice(format!("Congratulations, you are now level {}!", player_xp.level)).push();
+
+                if let Some(pos) = positions.get(*player_entity) {
+                    for x in pos.x - 1 ..= pos.x + 1 {
+                        particle_builder.request(x, pos.y - 1, rltk::RGB::named(rltk::GOLD), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('↑'), 400.0);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -43,7 +90,7 @@ pub fn delete_the_dead(ecs: &mut World) {
                     None => {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
-                            log.entries.push(format!("{} has died", &victim_name.name));
+                            log.add(format!("{} has died", &victim_name.name));
                         }
                         dead.push(entity)
                     },
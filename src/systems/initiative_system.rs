@@ -0,0 +1,73 @@
+use specs::prelude::*;
+use super::super::{
+    Initiative, MyTurn, StatusEffects, Position, RunState,
+};
+
+/// Energy an actor needs before it gets a turn; an actor at `NORMAL_SPEED`
+/// earns exactly this much per round
+pub const TURN_ENERGY : i32 = 100;
+pub const NORMAL_SPEED : i32 = 100;
+
+//?  What each kind of action costs out of the actor's energy
+pub const MOVE_COST : i32 = 100;
+pub const ATTACK_COST : i32 = 120;
+pub const USE_ITEM_COST : i32 = 80;
+pub const HANDLE_ITEM_COST : i32 = 50;
+pub const WAIT_COST : i32 = 100;
+
+//?  However slowed something is, it still gets to act eventually
+const MIN_SPEED : i32 = 10;
+
+/// Hands out energy until at least one actor on the level can afford a turn,
+/// then gives those actors `MyTurn`
+/// - Only runs while the game is `Ticking`; `run_systems` hands control to the
+///   player once the pass that gave them their turn has finished
+pub struct InitiativeSystem {}
+impl<'a> System<'a> for InitiativeSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        WriteStorage<'a, Initiative>,
+                        ReadStorage<'a, Position>,
+                        WriteStorage<'a, MyTurn>,
+                        ReadStorage<'a, StatusEffects>,
+                        ReadExpect<'a, RunState>, );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (
+            entities,
+            mut initiatives,
+            positions,
+            mut turns,
+            status_effects,
+            runstate,
+        ) = data;
+
+        if *runstate != RunState::Ticking { return; }
+        if (&initiatives, &positions).join().next().is_none() { return; }
+
+        let mut ready : Vec<Entity> = Vec::new();
+        loop {
+            for (entity, initiative, _pos) in (&entities, &initiatives, &positions).join() {
+                if initiative.energy >= TURN_ENERGY { ready.push(entity); }
+            }
+            if !ready.is_empty() { break; }
+
+            for (entity, initiative, _pos) in (&entities, &mut initiatives, &positions).join() {
+                let bonus = status_effects.get(entity).map_or(0, |status| status.speed_modifier());
+                initiative.energy += i32::max(initiative.speed + bonus, MIN_SPEED);
+            }
+        }
+
+        for entity in ready.iter() {
+            turns.insert(*entity, MyTurn{}).expect("Unable to insert turn");
+        }
+    }
+}
+
+/// Ends `entity`'s turn, paying `cost` energy for whatever it did
+pub fn spend_energy(initiatives : &mut WriteStorage<Initiative>, turns : &mut WriteStorage<MyTurn>, entity : Entity, cost : i32) {
+    if let Some(initiative) = initiatives.get_mut(entity) {
+        initiative.energy -= cost;
+    }
+    turns.remove(entity);
+}
@@ -3,10 +3,14 @@ use super::super::{
     Name, InBackpack, Position, gamelog::GameLog, CombatStats, Map, RunState,
     WantsToPickupItem, WantsToUseItem, WantsToDropItem, WantsToRemoveItem,
     SufferDamage, Equippable, Equipped, ProvidesWater, ThirstClock, ThirstState,
-    Consumable, ProvidesHealing, InflictsDamage, AreaOfEffect, Confusion,
-    MagicMapper, ParticleBuilder,
+    Consumable, ProvidesHealing, InflictsDamage, AreaOfEffect, InflictsStatus,
+    MagicMapper, ParticleBuilder, StatusEffects, StatusKind, Viewshed, Item, Rarity,
 };
 
+fn rarity_of(items : &ReadStorage<Item>, item : Entity) -> Rarity {
+    items.get(item).map_or(Rarity::Common, |i| i.rarity)
+}
+
 pub struct ItemCollectionSystem {}
 impl<'a> System<'a> for ItemCollectionSystem {
     #[allow(clippy::type_complexity)]
@@ -15,17 +19,20 @@ impl<'a> System<'a> for ItemCollectionSystem {
                         WriteStorage<'a, WantsToPickupItem>,
                         WriteStorage<'a, Position>,
                         ReadStorage<'a, Name>,
+                        ReadStorage<'a, Item>,
                         WriteStorage<'a, InBackpack>    );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (player_entity, mut gamelog, mut picksup, mut positions, names, mut backpack) = data;
+        let (player_entity, mut gamelog, mut picksup, mut positions, names, items, mut backpack) = data;
 
         for pickup in picksup.join() {
             positions.remove(pickup.item);
             backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by }).expect("Unable to insert backpack entry");
 
             if pickup.collected_by == *player_entity {
-                gamelog.entries.push(format!("You found a {}.", names.get(pickup.item).unwrap().name));
+                gamelog.entry().text("You found a ")
+                    .item(&names.get(pickup.item).unwrap().name, rarity_of(&items, pickup.item))
+                    .text(".").push();
             }
         }
 
@@ -46,7 +53,7 @@ impl<'a> System<'a> for ItemUseSystem {
                         ReadStorage<'a, InflictsDamage>,
                         ReadStorage<'a, ProvidesHealing>,
                         ReadStorage<'a, AreaOfEffect>,
-                        WriteStorage<'a, Confusion>,
+                        ReadStorage<'a, InflictsStatus>,
                         ReadStorage<'a, Equippable>,
                         WriteStorage<'a, Equipped>,
                         WriteStorage<'a, CombatStats>,
@@ -58,6 +65,9 @@ impl<'a> System<'a> for ItemUseSystem {
                         WriteStorage<'a, ThirstClock>,
                         ReadStorage<'a, MagicMapper>,
                         WriteExpect<'a, RunState>,
+                        WriteStorage<'a, StatusEffects>,
+                        WriteStorage<'a, Viewshed>,
+                        ReadStorage<'a, Item>,
                         );
 
     fn run(&mut self, data : Self::SystemData) {
@@ -72,7 +82,7 @@ impl<'a> System<'a> for ItemUseSystem {
             inflict_damage,
             healing,
             aoe,
-            mut confused,
+            inflicts_status,
             equippable,
             mut equipped,
             mut combat_stats,
@@ -84,6 +94,9 @@ impl<'a> System<'a> for ItemUseSystem {
             mut thirst_clock,
             magic_mapper,
             mut runstate,
+            mut status_effects,
+            mut viewsheds,
+            items,
         ) = data;
 
         for (entity, useitem) in (&entities, &using_item).join() {
@@ -135,7 +148,7 @@ impl<'a> System<'a> for ItemUseSystem {
                         if already_equipped.owner == target && already_equipped.slot == target_slot {
                             to_unequip.push(item_entity);
                             if target == *player_entity {
-                                gamelog.entries.push(format!("You unequip {}", name.name));
+                                gamelog.entry().text("You unequip ").item(&name.name, rarity_of(&items, item_entity)).push();
                             }
                         }
                     }
@@ -147,7 +160,8 @@ impl<'a> System<'a> for ItemUseSystem {
                     equipped.insert(useitem.item, Equipped{ owner: target, slot: target_slot }).expect("Unable to insert equipped component");
                     backpack.remove(useitem.item);
                     if target == *player_entity {
-                        gamelog.entries.push(format!("You equip {}", names.get(useitem.item).unwrap().name));
+                        gamelog.entry().text("You equip ")
+                            .item(&names.get(useitem.item).unwrap().name, rarity_of(&items, useitem.item)).push();
                     }
                 }
             }
@@ -162,7 +176,9 @@ impl<'a> System<'a> for ItemUseSystem {
                     if let Some(tc) = tc {
                         tc.state = ThirstState::Quenched;
                         tc.duration = 20;
-                        gamelog.entries.push(format!("{} quenches your thirst.", names.get(useitem.item).unwrap().name));
+                        gamelog.entry()
+                            .item(&names.get(useitem.item).unwrap().name, rarity_of(&items, useitem.item))
+                            .healing(" quenches your thirst.").push();
                     }
                 }
             }
@@ -176,7 +192,9 @@ impl<'a> System<'a> for ItemUseSystem {
                         if let Some(stats) = stats {
                             stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
                             if entity == *player_entity {
-                                gamelog.entries.push(format!("You eat the {}, restoring {} HP.", names.get(useitem.item).unwrap().name, healer.heal_amount));
+                                gamelog.entry().text("You eat the ")
+                                    .item(&names.get(useitem.item).unwrap().name, rarity_of(&items, useitem.item))
+                                    .text(", restoring ").healing(format!("{} HP", healer.heal_amount)).text(".").push();
                             }
                             used_item = true;
 
@@ -202,11 +220,13 @@ impl<'a> System<'a> for ItemUseSystem {
                 Some(damage) => {
                     used_item = false;
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage, entity == *player_entity);
                         if entity == *player_entity {
                             let mob_name = names.get(*mob).unwrap();
                             let item_name = names.get(useitem.item).unwrap();
-                            gamelog.entries.push(format!("You use the {} on the {}, inflicting {} damage.", item_name.name, mob_name.name, damage.damage));
+                            gamelog.entry().text("You use the ").item(&item_name.name, rarity_of(&items, useitem.item))
+                                .text(format!(" on the {}, inflicting ", mob_name.name))
+                                .damage(format!("{} damage", damage.damage)).text(".").push();
 
                             let pos = positions.get(*mob);
                             if let Some(pos) = pos {
@@ -231,45 +251,47 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => { }
                 Some(_) => {
                     used_item = true;
-                    gamelog.entries.push("You feel the shape of the walls.".to_string());
+                    gamelog.add("You feel the shape of the walls.");
                     *runstate = RunState::MapReveal{ row: 0 };
                 }
             }
 
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(useitem.item);
-                match causes_confusion {
-                    None => { }
-                    Some(confusion) => {
-                        used_item = false;
-                        for mob in targets.iter() {
-                            add_confusion.push(( *mob, confusion.turns ));
-                            if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
-                                gamelog.entries.push(format!("You use {} on {}, confusing them", item_name.name, mob_name.name));
-
-                                let pos = positions.get(*mob);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        rltk::RGB::named(rltk::MAGENTA),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('?'),
-                                        200.0,
-                                    );
-                                }
+            let item_status = inflicts_status.get(useitem.item);
+            match item_status {
+                None => { }
+                Some(status) => {
+                    let item_name = &names.get(useitem.item).unwrap().name;
+                    let rarity = rarity_of(&items, useitem.item);
+                    let adjective = status.effect.kind.adjective();
+                    for target in targets.iter() {
+                        StatusEffects::add_effect(&mut status_effects, *target, status.effect);
+                        if status.effect.kind == StatusKind::Blindness {
+                            if let Some(viewshed) = viewsheds.get_mut(*target) { viewshed.dirty = true; }
+                        }
+
+                        if entity == *player_entity {
+                            if *target == *player_entity {
+                                gamelog.entry().text("You use the ").item(item_name, rarity)
+                                    .text(". You are ").status(adjective).text("!").push();
+                            } else if let Some(target_name) = names.get(*target) {
+                                gamelog.entry().text("You use the ").item(item_name, rarity)
+                                    .text(format!(" on {}. It is ", target_name.name)).status(adjective).text("!").push();
                             }
                         }
+
+                        if let Some(pos) = positions.get(*target) {
+                            particle_builder.request(
+                                pos.x,
+                                pos.y,
+                                rltk::RGB::named(rltk::MAGENTA),
+                                rltk::RGB::named(rltk::BLACK),
+                                rltk::to_cp437('?'),
+                                200.0,
+                            );
+                        }
                     }
                 }
             }
-            for mob in add_confusion.iter() {
-                confused.insert(mob.0, Confusion{ turns: mob.1})
-                    .expect("Unable to insert confusion");
-            }
 
             if used_item {
                 let consumable = consumables.get(useitem.item);
@@ -294,11 +316,12 @@ impl <'a> System<'a> for ItemDropSystem {
                         Entities<'a>,
                         WriteStorage<'a, WantsToDropItem>,
                         ReadStorage<'a, Name>,
+                        ReadStorage<'a, Item>,
                         WriteStorage<'a, Position>,
                         WriteStorage<'a, InBackpack>    );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (player_entity, mut gamelog, entities, mut wants_drop, names, mut positions, mut backpack) = data;
+        let (player_entity, mut gamelog, entities, mut wants_drop, names, items, mut positions, mut backpack) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
             let mut dropper_pos : Position = Position{x:0,y:0};
@@ -312,7 +335,8 @@ impl <'a> System<'a> for ItemDropSystem {
             backpack.remove(to_drop.item);
 
             if entity == *player_entity {
-                gamelog.entries.push(format!("You drop the {}", names.get(to_drop.item).unwrap().name));
+                gamelog.entry().text("You drop the ")
+                    .item(&names.get(to_drop.item).unwrap().name, rarity_of(&items, to_drop.item)).push();
             }
         }
 
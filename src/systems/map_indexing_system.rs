@@ -1,6 +1,7 @@
 use specs::prelude::*;
+use std::collections::HashSet;
 use super::super::{
-    Map, Position, BlocksTile
+    Map, Position, BlocksTile, BlocksVisibility, Viewshed,
 };
 
 pub struct MapIndexingSystem {}
@@ -8,13 +9,16 @@ impl<'a> System<'a> for MapIndexingSystem {
     type SystemData = ( WriteExpect<'a, Map>,
                         ReadStorage<'a, Position>,
                         ReadStorage<'a, BlocksTile>,
+                        ReadStorage<'a, BlocksVisibility>,
+                        WriteStorage<'a, Viewshed>,
                         Entities<'a> );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, view_blockers, mut viewsheds, entities) = data;
 
         map.populate_blocked();
         map.clear_content_index();
+        let mut view_blocked : HashSet<usize> = HashSet::new();
         for (entity, position) in (&entities, &position).join() {
             let idx = map.xy_idx(position.x, position.y);
 
@@ -22,8 +26,17 @@ impl<'a> System<'a> for MapIndexingSystem {
             if let Some(_p) = _p {
                 map.blocked[idx] = true;
             }
+            if view_blockers.contains(entity) {
+                view_blocked.insert(idx);
+            }
 
             map.tile_content[idx].push(entity);
         }
+
+        //?  A door opening (or a level being entered) changes what everyone can see
+        if view_blocked != map.view_blocked {
+            map.view_blocked = view_blocked;
+            for viewshed in (&mut viewsheds).join() { viewshed.dirty = true; }
+        }
     }
-}
\ No newline at end of file
+}
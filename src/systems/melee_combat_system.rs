@@ -18,7 +18,8 @@ impl <'a> System<'a> for MeleeCombatSystem {
                         ReadStorage<'a, Equipped>,
                         WriteExpect<'a, ParticleBuilder>,
                         ReadStorage<'a, Position>,
-                        ReadStorage<'a, ThirstClock>   );
+                        ReadStorage<'a, ThirstClock>,
+                        ReadExpect<'a, Entity>   );
 
     fn run(&mut self, data : Self::SystemData) {
         let (
@@ -34,6 +35,7 @@ impl <'a> System<'a> for MeleeCombatSystem {
             mut particle_builder,
             positions,
             thirst_clock,
+            player_entity,
         ) = data;
 
         for (entity, does_melee, name, stats) in (&entities, &does_melee, &names, &combat_stats).join() {
@@ -73,10 +75,11 @@ impl <'a> System<'a> for MeleeCombatSystem {
                     let damage = i32::max(0, (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus));
 
                     if damage == 0 {
-                        log.entries.push(format!("{} cannot be touched by {}", &target_name.name, &name.name));
+                        log.add(format!("{} cannot be touched by {}", &target_name.name, &name.name));
                     } else {
-                        log.entries.push(format!("{} hugs {} for {} seconds", &name.name, &target_name.name, damage));
-                        SufferDamage::new_damage(&mut inflict_damage, does_melee.target, damage);
+                        log.entry().text(format!("{} hugs {} for ", &name.name, &target_name.name))
+                            .damage(format!("{} seconds", damage)).push();
+                        SufferDamage::new_damage(&mut inflict_damage, does_melee.target, damage, entity == *player_entity);
                     }
                 }
             }
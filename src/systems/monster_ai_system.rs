@@ -1,9 +1,11 @@
 use rltk::{ Point };
 use specs::prelude::*;
 use super::super::{
-    Viewshed, Monster, Map, Position, DoesMelee, RunState, Confusion,
-    ParticleBuilder, EntityMoved,
+    Viewshed, Monster, Map, Position, DoesMelee, StatusEffects, StatusKind,
+    ParticleBuilder, EntityMoved, Initiative, MyTurn, Door, OpensDoors, BlocksTile, BlocksVisibility,
+    Renderable,
 };
+use super::initiative_system::{ spend_energy, MOVE_COST, ATTACK_COST, WAIT_COST };
 
 pub struct MonsterAI {}
 impl<'a> System<'a> for MonsterAI {
@@ -11,42 +13,55 @@ impl<'a> System<'a> for MonsterAI {
     type SystemData = ( WriteExpect<'a, Map>,
                         ReadExpect<'a, Point>,
                         ReadExpect<'a, Entity>,
-                        ReadExpect<'a, RunState>,
                         Entities<'a>,
                         WriteStorage<'a, Viewshed>,
                         ReadStorage<'a, Monster>,
                         WriteStorage<'a, Position>,
                         WriteStorage<'a, DoesMelee>,
-                        WriteStorage<'a, Confusion>,
+                        ReadStorage<'a, StatusEffects>,
                         WriteExpect<'a, ParticleBuilder>,
-                        WriteStorage<'a, EntityMoved> );
+                        WriteStorage<'a, EntityMoved>,
+                        WriteStorage<'a, Initiative>,
+                        WriteStorage<'a, MyTurn>,
+                        WriteStorage<'a, Door>,
+                        ReadStorage<'a, OpensDoors>,
+                        WriteStorage<'a, BlocksTile>,
+                        WriteStorage<'a, BlocksVisibility>,
+                        WriteStorage<'a, Renderable> );
 
     fn run(&mut self, data : Self::SystemData) {
         let (
             mut map, player_pos,
             player_entity,
-            runstate,
             entities,
             mut viewshed,
             monster,
             mut position,
             mut does_melee,
-            mut confused,
+            status_effects,
             mut particle_builder,
             mut entity_moved,
+            mut initiatives,
+            mut turns,
+            mut doors,
+            opens_doors,
+            mut blocks_tile,
+            mut blocks_visibility,
+            mut renderables,
         ) = data;
 
-        if *runstate != RunState::MonsterTurn { return; }
+        let mut closed_doors : Vec<(Entity, usize)> = (&entities, &doors, &position).join()
+            .filter(|(_, door, _)| !door.open)
+            .map(|(door_entity, _, door_pos)| (door_entity, map.xy_idx(door_pos.x, door_pos.y)))
+            .collect();
 
-        for (entity, mut viewshed, _monster, mut pos) in (&entities, &mut viewshed, &monster, &mut position).join() {
+        let mut finished : Vec<(Entity, i32)> = Vec::new();
+        for (entity, viewshed, _monster, pos, _turn) in (&entities, &mut viewshed, &monster, &mut position, &turns).join() {
             let mut can_act = true;
+            let mut cost = WAIT_COST;
 
-            let is_confused = confused.get_mut(entity);
-            if let Some(i_am_confused) = is_confused {
-                i_am_confused.turns -= 1;
-                if i_am_confused.turns < 1 {
-                    confused.remove(entity);
-                }
+            let is_confused = status_effects.get(entity).is_some_and(|s| s.has(StatusKind::Confusion));
+            if is_confused {
                 can_act = false;
 
                 particle_builder.request(
@@ -63,14 +78,35 @@ impl<'a> System<'a> for MonsterAI {
                 let distance = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
                 if distance < 1.5 {
                     does_melee.insert(entity, DoesMelee{ target: *player_entity }).expect("Unable to insert attack");
+                    cost = ATTACK_COST;
                 }
                 else if viewshed.visible_tiles.contains(&*player_pos) {
+                    //?  Monsters that can open doors path straight through closed ones
+                    let can_open = opens_doors.get(entity).is_some();
+                    if can_open {
+                        for (_, door_idx) in closed_doors.iter() { map.blocked[*door_idx] = false; }
+                    }
                     let path = rltk::a_star_search(
                         map.xy_idx(pos.x, pos.y) as i32,
                         map.xy_idx(player_pos.x, player_pos.y) as i32,
                         &mut *map
                     );
-                    if path.success && path.steps.len() > 1 {
+                    if can_open {
+                        for (_, door_idx) in closed_doors.iter() { map.blocked[*door_idx] = true; }
+                    }
+                    let door_ahead = if path.success && path.steps.len() > 1 {
+                        closed_doors.iter().find(|(_, door_idx)| *door_idx == path.steps[1]).copied()
+                    } else { None };
+                    if let Some((door, door_idx)) = door_ahead {
+                        //?  Opening the door takes the move; the monster steps through next turn
+                        if Door::open_door(&mut doors, &mut blocks_tile, &mut blocks_visibility, &mut renderables, door) {
+                            cost = MOVE_COST;
+                            //?  Later monsters this turn see it open, rather than waiting on the map index
+                            closed_doors.retain(|(other, _)| *other != door);
+                            map.blocked[door_idx] = false;
+                        }
+                    }
+                    else if path.success && path.steps.len() > 1 {
                         let mut idx = map.xy_idx(pos.x, pos.y);
                         map.blocked[idx] = false;
                         pos.x = path.steps[1] as i32 % map.width;
@@ -79,9 +115,16 @@ impl<'a> System<'a> for MonsterAI {
                         idx = map.xy_idx(pos.x, pos.y);
                         map.blocked[idx] = true;
                         viewshed.dirty = true;
+                        cost = MOVE_COST;
                     }
                 }
             }
+
+            finished.push((entity, cost));
+        }
+
+        for (entity, cost) in finished {
+            spend_energy(&mut initiatives, &mut turns, entity, cost);
         }
     }
 }
@@ -5,12 +5,17 @@ use super::super::{
 };
 
 pub fn cull_dead_particles(ecs : &mut World, ctx : &Rltk) {
+    age_particles(ecs, ctx.frame_time_ms);
+}
+
+/// Ages every particle by `elapsed_ms` and deletes the ones that have expired
+pub fn age_particles(ecs : &mut World, elapsed_ms : f32) {
     let mut dead_particles : Vec<Entity> = Vec::new();
     {
         let mut particles = ecs.write_storage::<ParticleLifetime>();
         let entities = ecs.entities();
         for (entity, mut particle) in (&entities, &mut particles).join() {
-            particle.lifetime_ms -= ctx.frame_time_ms;
+            particle.lifetime_ms -= elapsed_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
@@ -1,3 +1,4 @@
+use serde::{ Serialize, Deserialize };
 use specs::prelude::*;
 use specs::saveload::{
     SimpleMarker,
@@ -9,11 +10,91 @@ use specs::saveload::{
 use std::convert::Infallible;
 use std::fs;
 use std::fs::File;
-use std::path::Path;
+use std::io::{ BufReader, BufWriter, Write };
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime, UNIX_EPOCH };
+use serde_json::Value;
 use super::super::components::*;
 
+/// Used when no `--save-dir` is given on the command line
+pub const DEFAULT_SAVE_DIR : &str = "./saves";
+pub const SAVE_SLOTS : usize = 5;
+
+/// The save format this build writes
+/// - Bump it whenever a saved component is changed in a way serde can't read
+///   as-is, and add the migration that upgrades the previous version
+pub const SAVE_VERSION : u32 = MIGRATIONS.len() as u32 + 1;
+
+//?  Every saved component, in the order they are written. Adding or reordering
+//?  entries is safe, since saves record the order they were written in; saves
+//?  holding a component that's been removed are refused, so removing one needs
+//?  a migration that drops it
+macro_rules! with_saved_components {
+    ($mac:ident, $( $arg:expr ),*) => {
+        $mac!(
+            $( $arg ),*,
+            Position, Renderable, Player, Viewshed, Monster, ParticleLifetime,
+            Name, BlocksTile, CombatStats, SufferDamage, DoesMelee,
+            ThirstClock, MeleePowerBonus, DefenseBonus, AreaOfEffect, StatusEffects, InflictsStatus,
+            Item, InBackpack, Consumable, Equippable, Equipped, Hidden,
+            Ranged, InflictsDamage, ProvidesHealing, ProvidesWater, MagicMapper,
+            WantsToPickupItem, WantsToUseItem, WantsToDropItem, WantsToRemoveItem,
+            EntityMoved, EntryTrigger, TriggersOnce, OtherLevelPosition,
+            Initiative, MyTurn, Experience, BlocksVisibility, Door, OpensDoors,
+            Bystander, Vendor,
+            SerializationHelper
+        )
+    };
+}
+
+macro_rules! component_names {
+    ($_unused:expr, $( $type:ty ),*) => { vec![ $( stringify!($type).to_string() ),* ] };
+}
+
+/// Written ahead of the world in every save, so the load menu can describe a
+/// slot without reading the whole file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveMetadata {
+    /// Saves from before the format was versioned are version 1
+    #[serde(default = "first_version")]
+    pub version : u32,
+    /// The saved components, in the order they follow the header
+    #[serde(default)]
+    pub components : Vec<String>,
+    pub character : String,
+    pub level : i32,
+    pub depth : i32,
+    pub turns : i32,
+    pub saved_at : u64,
+}
+
+fn first_version() -> u32 { 1 }
+
+pub enum SlotStatus {
+    Empty,
+    Saved(SaveMetadata),
+    Unreadable(String),
+}
+
+/// Reads `--save-dir <path>` or `--save-dir=<path>` from the command line
+pub fn save_dir_from_args() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--save-dir" {
+            return args.next().map(PathBuf::from);
+        } else if let Some(value) = arg.strip_prefix("--save-dir=") {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+pub fn slot_name(slot : usize) -> String { format!("Slot {}", slot + 1) }
+
+fn slot_path(dir : &Path, slot : usize) -> PathBuf { dir.join(format!("slot{}.json", slot + 1)) }
+
 macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
+    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty ),*) => {
         $(
             SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
                 &( $ecs.read_storage::<$type>(), ),
@@ -21,61 +102,229 @@ macro_rules! serialize_individually {
                 &$data.1,
                 &mut $ser,
             )
-            .unwrap();
+            .map_err(|e| e.to_string())?;
         )*
     };
 }
 
 #[cfg(not(target_arch = "wasm32"))] //?  Prevents web assembly trying to compile something it can't use
-pub fn save_game(ecs : &mut World) {
+pub fn save_game(ecs : &mut World, dir : &Path, slot : usize) -> Result<(), String> {
     let mapcopy = ecs.get_mut::<super::super::map::Map>().unwrap().clone();
+    let seed = ecs.fetch::<super::super::rng::RunSeed>().seed;
+    let dungeon = (*ecs.fetch::<super::super::dungeon::MasterDungeonMap>()).clone();
+    let turns = ecs.fetch::<super::super::TurnCounter>().turns;
+    let log = (*ecs.fetch::<super::super::GameLog>()).clone();
     let savehelper = ecs.create_entity()
-                        .with(SerializationHelper{ map : mapcopy })
+                        .with(SerializationHelper{ map : mapcopy, seed, dungeon, turns, log })
                         .marked::<SimpleMarker<SerializeMe>>()
                         .build();
 
+    let result = write_save(ecs, dir, slot);
+
+    ecs.delete_entity(savehelper).expect("Crash on cleanup");
+    result
+}
+
+//?  Writes next to the slot and renames over it, so a failed save never
+//?  costs the player the save that was already there
+#[cfg(not(target_arch = "wasm32"))]
+fn write_save(ecs : &mut World, dir : &Path, slot : usize) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Couldn't create {}: {}", dir.display(), e))?;
+    let path = slot_path(dir, slot);
+    let temp_path = path.with_extension("json.tmp");
+
     {
-        let data = ( ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>() );
+        let writer = File::create(&temp_path).map_err(|e| format!("Couldn't write {}: {}", temp_path.display(), e))?;
+        let mut writer = BufWriter::new(writer);
+        let mut serializer = serde_json::Serializer::new(&mut writer);
+        describe_save(ecs).serialize(&mut serializer).map_err(|e| e.to_string())?;
 
-        let writer = File::create("./savegame.json").unwrap();
-        let mut serializer = serde_json::Serializer::new(writer);
-        serialize_individually!(
-            ecs, serializer, data,
-            Position, Renderable, Player, Viewshed, Monster, ParticleLifetime,
-            Name, BlocksTile, CombatStats, SufferDamage, DoesMelee,
-            ThirstClock, MeleePowerBonus, DefenseBonus, AreaOfEffect, Confusion,
-            Item, InBackpack, Consumable, Equippable, Equipped, Hidden,
-            Ranged, InflictsDamage, ProvidesHealing, ProvidesWater, MagicMapper,
-            WantsToPickupItem, WantsToUseItem, WantsToDropItem, WantsToRemoveItem,
-            EntityMoved, EntryTrigger, TriggersOnce,
-            SerializationHelper
-        );
+        let data = ( ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>() );
+        with_saved_components!(serialize_individually, ecs, serializer, data);
+        writer.flush().map_err(|e| format!("Couldn't write {}: {}", temp_path.display(), e))?;
     }
 
-    ecs.delete_entity(savehelper).expect("Crash on cleanup");
+    fs::rename(&temp_path, &path).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))
 }
 
 #[cfg(target_arch = "wasm32")] //?  Protects from crashing when compiling to we assembly
-pub fn save_game(_ecs : &mut World) { }
+pub fn save_game(_ecs : &mut World, _dir : &Path, _slot : usize) -> Result<(), String> { Ok(()) }
+
+fn describe_save(ecs : &World) -> SaveMetadata {
+    let player_entity = *ecs.fetch::<Entity>();
+    SaveMetadata {
+        version : SAVE_VERSION,
+        components : with_saved_components!(component_names, ()),
+        character : ecs.read_storage::<Name>().get(player_entity).map_or("Unknown".to_string(), |n| n.name.clone()),
+        level : ecs.read_storage::<Experience>().get(player_entity).map_or(1, |xp| xp.level),
+        depth : ecs.fetch::<super::super::map::Map>().depth,
+        turns : ecs.fetch::<super::super::TurnCounter>().turns,
+        saved_at : SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+    }
+}
+
+pub fn any_saves(dir : &Path) -> bool {
+    (0..SAVE_SLOTS).any(|slot| slot_path(dir, slot).exists())
+}
+
+/// Describes what is in a slot by reading just the start of its save
+pub fn slot_status(dir : &Path, slot : usize) -> SlotStatus {
+    let path = slot_path(dir, slot);
+    if !path.exists() { return SlotStatus::Empty; }
 
-pub fn does_save_exist() -> bool { Path::new("./savegame.json").exists() }
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) => return SlotStatus::Unreadable(e.to_string()),
+    };
+    let mut stream = serde_json::Deserializer::from_reader(BufReader::new(file)).into_iter::<SaveMetadata>();
+    match stream.next() {
+        Some(Ok(metadata)) if metadata.version > SAVE_VERSION => SlotStatus::Unreadable(newer_version(metadata.version)),
+        Some(Ok(metadata)) => SlotStatus::Saved(metadata),
+        Some(Err(e)) => SlotStatus::Unreadable(e.to_string()),
+        None => SlotStatus::Unreadable("the file is empty".to_string()),
+    }
+}
+
+fn newer_version(version : u32) -> String {
+    format!("saved by a newer version of the game (format {}, this one reads up to {})", version, SAVE_VERSION)
+}
+
+/// A save read back as plain JSON, so older formats can be reshaped before
+/// anything is deserialized for real
+pub struct SaveFile {
+    pub header : Value,
+    /// One entry per saved component, in the order the header lists them
+    pub storages : Vec<Value>,
+}
+
+type Migration = fn(&mut SaveFile) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` upgrades a version `n + 1` save to version `n + 2`
+const MIGRATIONS : [Migration; 2] = [
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+];
+
+/// The components version 1 saves were written with, in order
+const V1_COMPONENTS : [&str; 40] = [
+    "Position", "Renderable", "Player", "Viewshed", "Monster", "ParticleLifetime",
+    "Name", "BlocksTile", "CombatStats", "SufferDamage", "DoesMelee",
+    "ThirstClock", "MeleePowerBonus", "DefenseBonus", "AreaOfEffect", "StatusEffects", "InflictsStatus",
+    "Item", "InBackpack", "Consumable", "Equippable", "Equipped", "Hidden",
+    "Ranged", "InflictsDamage", "ProvidesHealing", "ProvidesWater", "MagicMapper",
+    "WantsToPickupItem", "WantsToUseItem", "WantsToDropItem", "WantsToRemoveItem",
+    "EntityMoved", "EntryTrigger", "TriggersOnce", "OtherLevelPosition",
+    "Initiative", "MyTurn", "Experience",
+    "SerializationHelper",
+];
+
+/// Version 1 saves didn't say which components they held, so name them from
+/// the list the game used at the time
+fn migrate_v1_to_v2(save : &mut SaveFile) -> Result<(), String> {
+    if save.storages.len() != V1_COMPONENTS.len() {
+        return Err(format!("expected {} components, found {}", V1_COMPONENTS.len(), save.storages.len()));
+    }
+    save.header["components"] = V1_COMPONENTS.iter().map(|name| Value::from(*name)).collect();
+    Ok(())
+}
+
+fn not_json(e : serde_json::Error) -> String { format!("it isn't valid JSON ({})", e) }
+
+//?  The saved data of every entity that has `component`; entities without it
+//?  are written as `null`
+fn saved_components<'a>(save : &'a mut SaveFile, component : &str) -> Result<Vec<&'a mut Value>, String> {
+    let idx = save.header["components"].as_array()
+        .and_then(|names| names.iter().position(|name| name == component))
+        .ok_or_else(|| format!("it has no {} storage", component))?;
+    let entities = save.storages[idx].as_array_mut().ok_or_else(|| format!("its {} storage isn't a list", component))?;
+    Ok(entities.iter_mut()
+        .filter_map(|entity| entity.pointer_mut("/components/0"))
+        .filter(|component| !component.is_null())
+        .collect())
+}
+
+/// Version 3 gave items a rarity and started saving the game log; older
+/// items count as common, and the log starts out empty
+fn migrate_v2_to_v3(save : &mut SaveFile) -> Result<(), String> {
+    for item in saved_components(save, "Item")? {
+        item["rarity"] = Value::from("Common");
+    }
+    let turns = save.header["turns"].clone();
+    for helper in saved_components(save, "SerializationHelper")? {
+        helper["log"] = serde_json::json!({ "entries" : [], "turn" : turns });
+    }
+    Ok(())
+}
+
+/// Splits a save into its header and components and upgrades it to
+/// `SAVE_VERSION`
+pub fn read_save(data : &str) -> Result<(SaveMetadata, SaveFile), String> {
+    let mut values = serde_json::Deserializer::from_str(data).into_iter::<Value>();
+    let header = values.next().ok_or("the file is empty")?.map_err(not_json)?;
+    let storages = values.collect::<Result<Vec<Value>, _>>().map_err(not_json)?;
+    let mut save = SaveFile{ header, storages };
+
+    let mut version = save.header.get("version").map_or(Some(1), |v| v.as_u64()).ok_or("its format version isn't a number")? as u32;
+    if version > SAVE_VERSION { return Err(newer_version(version)); }
+    if version == 0 { return Err("its format version is 0".to_string()); }
+    while version < SAVE_VERSION {
+        MIGRATIONS[version as usize - 1](&mut save)
+            .map_err(|e| format!("couldn't upgrade it from format {}: {}", version, e))?;
+        version += 1;
+        save.header["version"] = Value::from(version);
+    }
+
+    let metadata : SaveMetadata = serde_json::from_value(save.header.clone())
+        .map_err(|e| format!("its header is damaged ({})", e))?;
+    if metadata.components.len() != save.storages.len() {
+        return Err(format!("its header lists {} components but it holds {}", metadata.components.len(), save.storages.len()));
+    }
+    Ok((metadata, save))
+}
+
+//?  Lines the saved storages up with the components this build saves; any
+//?  component the save predates starts out empty
+fn align_storages(metadata : &SaveMetadata, mut save : SaveFile) -> Result<Vec<Value>, String> {
+    let current : Vec<String> = with_saved_components!(component_names, ());
+    if let Some(unknown) = metadata.components.iter().find(|name| !current.contains(name)) {
+        return Err(format!("it holds {}, which this version of the game doesn't know", unknown));
+    }
+    Ok(current.iter().map(|name| {
+        match metadata.components.iter().position(|saved| saved == name) {
+            Some(idx) => std::mem::take(&mut save.storages[idx]),
+            None => Value::Array(Vec::new()),
+        }
+    }).collect())
+}
 
 macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+    ($ecs:expr, $storages:expr, $data:expr, $( $type:ty ),*) => {
         $(
             DeserializeComponents::<Infallible, _>::deserialize(
                 &mut ( &mut $ecs.write_storage::<$type>(), ),
                 &mut $data.0,
                 &mut $data.1,
                 &mut $data.2,
-                &mut $de,
+                $storages.next().expect("Storages are aligned to the component list"),
             )
-            .unwrap();
+            .map_err(|e| e.to_string())?;
         )*
     };
 }
 
-pub fn load_game(ecs: &mut World) {
+/// Replaces the world with the one saved in `slot`
+/// - The file is checked and upgraded before anything is cleared, so a
+///   missing, mangled or incompatible save leaves the current world alone;
+///   only a save that parses but doesn't describe a world can fail after
+///   that, and leaves the world empty
+pub fn load_game(ecs : &mut World, dir : &Path, slot : usize) -> Result<(), String> {
+    let path = slot_path(dir, slot);
+    let cant_load = |e : String| format!("Can't load {}: {}", slot_name(slot), e);
+    let data = fs::read_to_string(&path).map_err(|e| cant_load(format!("couldn't read {} ({})", path.display(), e)))?;
+
+    let (metadata, save) = read_save(&data).map_err(cant_load)?;
+    let storages = align_storages(&metadata, save).map_err(cant_load)?;
+
     {
         let mut to_delete = Vec::new();
         for e in ecs.entities().join() { to_delete.push(e); }
@@ -84,29 +333,22 @@ pub fn load_game(ecs: &mut World) {
         }
     }
 
-    let data = fs::read_to_string("./savegame.json").unwrap();
+    load_world(ecs, storages).map_err(|e| cant_load(format!("it is damaged ({})", e)))
+}
 
-    let mut de = serde_json::Deserializer::from_str(&data);
+fn load_world(ecs : &mut World, storages : Vec<Value>) -> Result<(), String> {
+    let mut storages = storages.into_iter();
     {
         let mut d = (
             &mut ecs.entities(),
             &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
             &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>()
         );
-        deserialize_individually!(
-            ecs, de, d,
-            Position, Renderable, Player, Viewshed, Monster, ParticleLifetime,
-            Name, BlocksTile, CombatStats, SufferDamage, DoesMelee,
-            ThirstClock, MeleePowerBonus, DefenseBonus, AreaOfEffect, Confusion,
-            Item, InBackpack, Consumable, Equippable, Equipped, Hidden,
-            Ranged, InflictsDamage, ProvidesHealing, ProvidesWater, MagicMapper,
-            WantsToPickupItem, WantsToUseItem, WantsToDropItem, WantsToRemoveItem,
-            EntityMoved, EntryTrigger, TriggersOnce,
-            SerializationHelper
-        );
+        with_saved_components!(deserialize_individually, ecs, storages, d);
     }
 
     let mut deleteme : Option<Entity> = None;
+    let mut found_player = false;
     {
         let entities = ecs.entities();
         let helper = ecs.read_storage::<SerializationHelper>();
@@ -115,7 +357,15 @@ pub fn load_game(ecs: &mut World) {
         for (e,h) in (&entities, &helper).join() {
             let mut worldmap = ecs.write_resource::<super::super::map::Map>();
             *worldmap = h.map.clone();
-            worldmap.tile_content = vec![Vec::new(); super::super::map::MAPCOUNT];
+            worldmap.reset_content_index();
+            let mut run_seed = ecs.write_resource::<super::super::rng::RunSeed>();
+            run_seed.seed = h.seed;
+            let mut dungeon = ecs.write_resource::<super::super::dungeon::MasterDungeonMap>();
+            *dungeon = h.dungeon.clone();
+            ecs.write_resource::<super::super::TurnCounter>().turns = h.turns;
+            *ecs.write_resource::<super::super::GameLog>() = h.log.clone();
+            let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+            *rng = super::super::rng::gameplay_rng(h.seed, h.map.depth);
             deleteme = Some(e);
         }
         for (e,_p,pos) in (&entities, &player, &position).join() {
@@ -123,13 +373,39 @@ pub fn load_game(ecs: &mut World) {
             *ppos = rltk::Point::new(pos.x, pos.y);
             let mut player_resource = ecs.write_resource::<Entity>();
             *player_resource = e;
+            found_player = true;
         }
     }
-    ecs. delete_entity(deleteme.unwrap()).expect("Unable to delete deserializer")
+
+    let deleteme = deleteme.ok_or("it has no map")?;
+    ecs.delete_entity(deleteme).expect("Unable to delete deserializer");
+    if !found_player { return Err("it has no player".to_string()); }
+    Ok(())
 }
 
-pub fn delete_save() {
-    if Path::new("./savegame.json").exists() {
-        std::fs::remove_file("./savegame.json").expect("Unable to delete save file");
+pub fn delete_save(dir : &Path, slot : usize) {
+    let path = slot_path(dir, slot);
+    if path.exists() {
+        //?  Losing the file is what we wanted anyway, so a failure here isn't worth reporting
+        let _ = fs::remove_file(path);
     }
 }
+
+/// Formats seconds since the Unix epoch as "YYYY-MM-DD HH:MM" in UTC
+pub fn format_timestamp(secs : u64) -> String {
+    let days = (secs / 86400) as i64;
+    let (hour, minute) = ((secs % 86400) / 3600, (secs % 3600) / 60);
+
+    //?  Howard Hinnant's days-to-civil algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
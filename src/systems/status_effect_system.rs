@@ -0,0 +1,91 @@
+use specs::prelude::*;
+use super::super::{
+    StatusEffects, StatusKind, MyTurn, SufferDamage, CombatStats, Name, Viewshed,
+    Position, Map, RunState, GameLog, systems::particle_system::ParticleBuilder,
+};
+
+/// Ticks every status effect on whoever has just been handed a turn: poison
+/// and bleeding hurt, regeneration heals, and anything that has run its
+/// course wears off
+pub struct StatusEffectSystem {}
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( Entities<'a>,
+                        ReadStorage<'a, MyTurn>,
+                        WriteStorage<'a, StatusEffects>,
+                        WriteStorage<'a, SufferDamage>,
+                        WriteStorage<'a, CombatStats>,
+                        ReadStorage<'a, Name>,
+                        WriteStorage<'a, Viewshed>,
+                        ReadStorage<'a, Position>,
+                        ReadExpect<'a, Map>,
+                        ReadExpect<'a, Entity>,
+                        ReadExpect<'a, RunState>,
+                        WriteExpect<'a, GameLog>,
+                        WriteExpect<'a, ParticleBuilder>, );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (
+            entities,
+            turns,
+            mut status_effects,
+            mut inflict_damage,
+            mut combat_stats,
+            names,
+            mut viewsheds,
+            positions,
+            map,
+            player_entity,
+            runstate,
+            mut log,
+            mut particle_builder,
+        ) = data;
+
+        if *runstate != RunState::Ticking { return; }
+
+        let mut cleared : Vec<Entity> = Vec::new();
+        for (entity, status, _turn) in (&entities, &mut status_effects, &turns).join() {
+            let pos = positions.get(entity);
+            let is_player = entity == *player_entity;
+            let in_view = is_player || pos.is_some_and(|pos| map.visible_tiles[map.xy_idx(pos.x, pos.y)]);
+
+            for effect in status.effects.iter_mut() {
+                match effect.kind {
+                    StatusKind::Poison | StatusKind::Bleeding => {
+                        SufferDamage::new_damage(&mut inflict_damage, entity, effect.magnitude, false);
+                        if let Some(pos) = pos {
+                            let fg = if effect.kind == StatusKind::Poison { rltk::GREEN } else { rltk::RED };
+                            particle_builder.request(pos.x, pos.y, rltk::RGB::named(fg), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
+                        }
+                    }
+                    StatusKind::Regeneration => {
+                        if let Some(stats) = combat_stats.get_mut(entity) {
+                            stats.hp = i32::min(stats.max_hp, stats.hp + effect.magnitude);
+                        }
+                    }
+                    _ => { }
+                }
+                effect.turns -= 1;
+            }
+
+            for effect in status.effects.iter().filter(|e| e.turns < 1) {
+                if is_player {
+                    log.entry().text("You are no longer ").status(effect.kind.adjective()).text(".").push();
+                } else if in_view {
+                    if let Some(name) = names.get(entity) {
+                        log.entry().text(format!("{} is no longer ", name.name)).status(effect.kind.adjective()).text(".").push();
+                    }
+                }
+                if effect.kind == StatusKind::Blindness {
+                    if let Some(viewshed) = viewsheds.get_mut(entity) { viewshed.dirty = true; }
+                }
+            }
+            status.effects.retain(|e| e.turns > 0);
+            if status.effects.is_empty() { cleared.push(entity); }
+        }
+
+        for entity in cleared {
+            status_effects.remove(entity);
+        }
+    }
+}
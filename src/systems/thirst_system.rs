@@ -29,7 +29,7 @@ impl<'a> System<'a> for ThirstSystem {
                 RunState::PlayerTurn => {
                     if entity == *player_entity { proceed = true; }
                 }
-                RunState::MonsterTurn => {
+                RunState::Ticking => {
                     if entity != *player_entity { proceed = true; }
                 }
                 _ => proceed = false
@@ -43,28 +43,28 @@ impl<'a> System<'a> for ThirstSystem {
                             clock.state = ThirstState::Normal;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are no longer quenched.".to_string());
+                                log.add("You are no longer quenched.");
                             }
                         }
                         ThirstState::Normal => {
                             clock.state = ThirstState::Thirsty;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are thirsty.".to_string());
+                                log.add("You are thirsty.");
                             }
                         }
                         ThirstState::Thirsty => {
                             clock.state = ThirstState::Parched;
                             clock.duration = 200;
                             if entity == *player_entity {
-                                log.entries.push("You are dangerously dehydrated.".to_string());
+                                log.add("You are dangerously dehydrated.");
                             }
                         }
                         ThirstState::Parched => {
                             if entity == *player_entity {
-                                log.entries.push("Your thirst hurts.".to_string());
+                                log.add("Your thirst hurts.");
                             }
-                            SufferDamage::new_damage(&mut inflict_damage, entity, 2);
+                            SufferDamage::new_damage(&mut inflict_damage, entity, 2, false);
                         }
                     }
                 }
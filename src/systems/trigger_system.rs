@@ -2,6 +2,7 @@ use specs::prelude::*;
 use super::super::{
     EntityMoved, Position, EntryTrigger, Hidden, Map, Name, InflictsDamage,
     ParticleBuilder, SufferDamage, TriggersOnce, gamelog::GameLog,
+    InflictsStatus, StatusEffects,
 };
 
 pub struct TriggerSystem { }
@@ -19,6 +20,8 @@ impl<'a> System<'a> for TriggerSystem {
                         WriteExpect<'a, ParticleBuilder>,
                         WriteStorage<'a, SufferDamage>,
                         ReadStorage<'a, TriggersOnce>,
+                        ReadStorage<'a, InflictsStatus>,
+                        WriteStorage<'a, StatusEffects>,
                         );
 
     fn run(&mut self, data : Self::SystemData) {
@@ -35,6 +38,8 @@ impl<'a> System<'a> for TriggerSystem {
             mut particle_builder,
             mut inflict_damage,
             triggers_once,
+            inflicts_status,
+            mut status_effects,
         ) = data;
 
         let mut remove_entities : Vec::<Entity> = Vec::new();
@@ -48,7 +53,7 @@ impl<'a> System<'a> for TriggerSystem {
                         Some(_trigger) => {
                             let name = names.get(*entity_id);
                             if let Some(name) = name {
-                                log.entries.push(format!("{} triggered!", &name.name));
+                                log.entry().damage(&name.name).text(" triggered!").push();
                             }
 
                             hidden.remove(*entity_id);
@@ -56,7 +61,11 @@ impl<'a> System<'a> for TriggerSystem {
                             let damage = inflicts_damage.get(*entity_id);
                             if let Some(damage) = damage {
                                 particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
-                                SufferDamage::new_damage(&mut inflict_damage, entity, damage.damage)
+                                SufferDamage::new_damage(&mut inflict_damage, entity, damage.damage, false)
+                            }
+
+                            if let Some(status) = inflicts_status.get(*entity_id) {
+                                StatusEffects::add_effect(&mut status_effects, entity, status.effect);
                             }
 
                             let to = triggers_once.get(*entity_id);
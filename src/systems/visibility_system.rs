@@ -2,6 +2,7 @@ use rltk::{ field_of_view, Point };
 use specs::prelude::*;
 use super::super::{
     Viewshed, Position, Map, Player, Hidden, gamelog::GameLog, Name,
+    StatusEffects, StatusKind,
 };
 
 pub struct VisibilitySystem {}
@@ -16,6 +17,7 @@ impl<'a> System<'a> for VisibilitySystem {
                         WriteStorage<'a, Hidden>,
                         WriteExpect<'a, GameLog>,
                         ReadStorage<'a, Name>,
+                        ReadStorage<'a, StatusEffects>,
                         );
 
     fn run(&mut self, data : Self::SystemData) {
@@ -29,13 +31,16 @@ impl<'a> System<'a> for VisibilitySystem {
             mut hidden,
             mut log,
             names,
+            status_effects,
         ) = data;
 
         for (ent,viewshed,pos) in (&entities, &mut viewshed, &pos).join() {
             if viewshed.dirty {
                 viewshed.dirty = false;
                 // viewshed.visible_tiles.clear();
-                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), viewshed.range, &*map);
+                let blind = status_effects.get(ent).is_some_and(|s| s.has(StatusKind::Blindness));
+                let range = if blind { 1 } else { viewshed.range };
+                viewshed.visible_tiles = field_of_view(Point::new(pos.x, pos.y), range, &*map);
                 viewshed.visible_tiles.retain( |p| p.x >= 0 && p.x < map.width && p.y >= 0 && p.y < map.height );
 
                 let _p : Option<&Player> = player.get(ent);
@@ -52,7 +57,7 @@ impl<'a> System<'a> for VisibilitySystem {
                                 if rng.roll_dice(1,24) == 1 {
                                     let name = names.get(*e);
                                     if let Some(name) = name {
-                                        log.entries.push(format!("You spot a {}.", &name.name));
+                                        log.entry().text("You spot a ").notice(&name.name).text(".").push();
                                     }
                                     hidden.remove(*e);
                                 }
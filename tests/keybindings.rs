@@ -0,0 +1,29 @@
+use hellorust::keybindings::{ Keybindings, Action, Preset };
+use rltk::VirtualKeyCode;
+
+#[test]
+fn every_preset_binds_every_action() {
+    for preset in [Preset::Classic, Preset::Vi, Preset::Numpad, Preset::Arrows] {
+        let bindings = Keybindings::preset(preset);
+        for action in Action::ALL {
+            assert!(!bindings.keys_for(action).is_empty(), "{:?} leaves {:?} unbound", preset, action);
+        }
+    }
+}
+
+#[test]
+fn config_files_override_their_preset() {
+    let bindings = Keybindings::parse(r#"{ "preset" : "Vi", "bindings" : { "q" : "SaveAndQuit", "Escape" : null } }"#).unwrap();
+
+    assert_eq!(bindings.action_for(VirtualKeyCode::H), Some(Action::MoveWest));
+    assert_eq!(bindings.action_for(VirtualKeyCode::Q), Some(Action::SaveAndQuit));
+    assert_eq!(bindings.action_for(VirtualKeyCode::Escape), None);
+    assert_eq!(bindings.keys_for(Action::SaveAndQuit), vec!["Q".to_string()]);
+}
+
+#[test]
+fn bad_config_files_are_reported() {
+    assert!(Keybindings::parse(r#"{ "bindings" : { "Hyper" : "Wait" } }"#).unwrap_err().contains("Hyper"));
+    assert!(Keybindings::parse(r#"{ "bindings" : { "Q" : "Teleport" } }"#).is_err());
+    assert!(Keybindings::parse(r#"{ "preset" : "Emacs" }"#).is_err());
+}
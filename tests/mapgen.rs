@@ -0,0 +1,113 @@
+use hellorust::map_builders::{ self, BuilderChain, RANDOM_CHAINS };
+use hellorust::{ Map, TileType, rng, mapgen_stats };
+use hellorust::mapgen_visualizer::MapgenVisualizer;
+
+const SEEDS : u64 = 10;
+
+/// Walkable tiles the player can't get to from the start, and where the
+/// down stairs are
+fn check_level(map : &mut Map, start_idx : usize) -> (Vec<usize>, Vec<usize>) {
+    map.populate_blocked();
+    let max_depth = (map.width * map.height) as f32;
+    let dijkstra = rltk::DijkstraMap::new(map.width as usize, map.height as usize, &[start_idx], &*map, max_depth);
+    let unreachable = (0..map.tiles.len()).filter(|idx| map.tiles[*idx].walkable() && dijkstra.map[*idx] == f32::MAX).collect();
+    let stairs = (0..map.tiles.len()).filter(|idx| map.tiles[*idx] == TileType::DownStairs).collect();
+    (unreachable, stairs)
+}
+
+fn assert_connected(name : &str, seed : u64, builder : &mut BuilderChain) {
+    let start = builder.build_data.starting_position.clone().unwrap_or_else(|| panic!("{} (seed {}) has no start", name, seed));
+    let map = &mut builder.build_data.map;
+    let start_idx = map.xy_idx(start.x, start.y);
+    assert!(map.tiles[start_idx].walkable(), "{} (seed {}) starts in a wall", name, seed);
+    let (unreachable, stairs) = check_level(map, start_idx);
+    assert!(unreachable.is_empty(), "{} (seed {}) leaves {} tiles out of reach", name, seed, unreachable.len());
+    assert_eq!(stairs.len(), 1, "{} (seed {}) should have exactly one way down", name, seed);
+}
+
+/// Everything in the spawn list is something the raws can spawn, with at
+/// most one thing on each tile and nothing where the player starts
+fn assert_spawns(name : &str, seed : u64, builder : &BuilderChain) {
+    let spawns = &builder.build_data.spawn_list;
+    let start = builder.build_data.starting_position.clone().unwrap();
+    let start_idx = builder.build_data.map.xy_idx(start.x, start.y);
+    assert!(spawns.iter().all(|(idx, _)| *idx != start_idx), "{} (seed {}) spawns something on the start", name, seed);
+    assert!(spawns.iter().all(|(_, entity)| entity != "None"), "{} (seed {}) lists an empty roll as a spawn", name, seed);
+    let mut tiles : Vec<usize> = spawns.iter().map(|(idx, _)| *idx).collect();
+    tiles.sort_unstable();
+    tiles.dedup();
+    assert_eq!(tiles.len(), spawns.len(), "{} (seed {}) spawns two things on one tile", name, seed);
+}
+
+#[test]
+fn every_random_chain_builds_connected_maps() {
+    hellorust::raws::load_raws().unwrap();
+    for (chain, name) in RANDOM_CHAINS.iter().enumerate() {
+        for seed in 0..SEEDS {
+            //?  Alternate depths so both the first size and a grown map are covered
+            let depth = if seed % 2 == 0 { 2 } else { 7 };
+            let (width, height) = map_builders::level_size(depth);
+            let mut rng = rng::map_rng(seed, depth);
+            let mut builder = map_builders::chain_builder(chain, depth, &mut rng, width, height, false);
+            builder.build_map(&mut rng);
+            assert_connected(name, seed, &mut builder);
+            assert_spawns(name, seed, &builder);
+        }
+    }
+}
+
+#[test]
+fn the_fixed_levels_are_connected() {
+    for depth in [1, 3] {
+        let (width, height) = map_builders::level_size(depth);
+        let mut rng = rng::map_rng(0, depth);
+        let mut builder = map_builders::level_builder(depth, &mut rng, width, height, false);
+        builder.build_map(&mut rng);
+        assert_connected(&format!("Depth {}", depth), 0, &mut builder);
+    }
+}
+
+#[test]
+fn the_visualizer_replays_builders_by_seed() {
+    let mut vis = MapgenVisualizer::new(7);
+    assert_eq!(vis.builder_name(), "Level builder");
+    assert!(vis.history.len() > 1);
+
+    vis.select_chain(-1);
+    assert_eq!(vis.builder_name(), "Simple Map");
+    vis.select_depth(1);
+    let first : Vec<Vec<TileType>> = vis.history.iter().map(|m| m.tiles.clone()).collect();
+    vis.regenerate();
+    let again : Vec<Vec<TileType>> = vis.history.iter().map(|m| m.tiles.clone()).collect();
+    assert!(first == again);
+
+    //?  The last snapshot is the map the game would get
+    let (width, height) = map_builders::level_size(2);
+    let mut rng = rng::map_rng(7, 2);
+    let mut builder = map_builders::chain_builder(RANDOM_CHAINS.len() - 1, 2, &mut rng, width, height, false);
+    builder.build_map(&mut rng);
+    assert!(vis.history.last().unwrap().tiles == builder.build_data.map.tiles);
+
+    vis.step(1000);
+    assert!(vis.paused);
+    assert_eq!(vis.index, vis.history.len() - 1);
+    vis.step(-1000);
+    assert_eq!(vis.index, 0);
+    vis.update(10_000.0);
+    assert_eq!(vis.index, 0);
+}
+
+#[test]
+fn stats_measure_each_run_of_a_builder() {
+    hellorust::raws::load_raws().unwrap();
+    let report = mapgen_stats::report(Some(RANDOM_CHAINS.len() - 1), 2, 1, 3);
+    assert_eq!(report.name, "Simple Map");
+    assert_eq!(report.runs.len(), 3);
+    assert_eq!(report.failures(), 0);
+    for run in report.runs.iter() {
+        assert_eq!(run.regions, 1);
+        assert!(run.stairs_distance.unwrap() > 0.0);
+        assert!(run.floor_percent > 0.0 && run.floor_percent < 100.0);
+        assert!(run.mobs + run.items + run.props > 0);
+    }
+}
@@ -0,0 +1,582 @@
+use hellorust::headless::Simulation;
+use hellorust::raws::{ RAWS, SpawnType, spawn_named_entity };
+use hellorust::*;
+use rltk::{ Point, BaseMap };
+use specs::prelude::*;
+
+const SEED : u64 = 1234;
+
+fn player_pos(sim : &Simulation) -> Point { *sim.ecs.fetch::<Point>() }
+
+fn player_hp(sim : &Simulation) -> i32 {
+    sim.ecs.read_storage::<CombatStats>().get(sim.player()).unwrap().hp
+}
+
+/// Moves the player onto `target` without spending a turn
+fn teleport_player(sim : &mut Simulation, target : Point) {
+    let player = sim.player();
+    sim.ecs.write_storage::<Position>().insert(player, Position{ x: target.x, y: target.y }).unwrap();
+    *sim.ecs.write_resource::<Point>() = target;
+}
+
+/// Deletes every monster so the test only sees what it spawns itself
+fn clear_monsters(sim : &mut Simulation) {
+    let monsters : Vec<Entity> = {
+        let entities = sim.ecs.entities();
+        let monsters = sim.ecs.read_storage::<Monster>();
+        (&entities, &monsters).join().map(|(e, _m)| e).collect()
+    };
+    for monster in monsters { sim.ecs.delete_entity(monster).unwrap(); }
+    run_systems(&mut sim.ecs);
+}
+
+/// First walkable, empty tile next to the player, as a step direction
+fn open_neighbour(sim : &Simulation) -> (i32, i32) {
+    let pos = player_pos(sim);
+    let map = sim.ecs.fetch::<Map>();
+    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, -1), (1, -1), (-1, 1)] {
+        let idx = map.xy_idx(pos.x + dx, pos.y + dy);
+        if map.tiles[idx].walkable() && map.tile_content[idx].is_empty() { return (dx, dy); }
+    }
+    panic!("Player is boxed in");
+}
+
+fn spawn(sim : &mut Simulation, name : &str, x : i32, y : i32) -> Entity {
+    let entity = spawn_named_entity(&RAWS.lock().unwrap(), &mut sim.ecs, name, SpawnType::AtPosition{ x, y });
+    run_systems(&mut sim.ecs);
+    entity.unwrap()
+}
+
+#[test]
+fn same_seed_builds_the_same_dungeon() {
+    let a = Simulation::new(SEED).unwrap();
+    let b = Simulation::new(SEED).unwrap();
+
+    assert!(a.ecs.fetch::<Map>().tiles == b.ecs.fetch::<Map>().tiles);
+    assert_eq!(player_pos(&a), player_pos(&b));
+}
+
+#[test]
+fn attacking_a_goblin_kills_it() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let (dx, dy) = open_neighbour(&sim);
+    let pos = player_pos(&sim);
+    let goblin = spawn(&mut sim, "Goblin", pos.x + dx, pos.y + dy);
+
+    for _ in 0..20 {
+        sim.step(PlayerCommand::Move{ dx, dy });
+        if !sim.ecs.is_alive(goblin) { break; }
+    }
+
+    assert!(!sim.ecs.is_alive(goblin));
+    assert_eq!(sim.run_state(), RunState::AwaitingInput);
+}
+
+#[test]
+fn picking_up_and_eating_a_heart_heals() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let pos = player_pos(&sim);
+    let heart = spawn(&mut sim, "Bloody Heart", pos.x, pos.y);
+    let player = sim.player();
+    sim.ecs.write_storage::<CombatStats>().get_mut(player).unwrap().hp = 10;
+
+    sim.step(PlayerCommand::PickUp);
+    assert_eq!(sim.ecs.read_storage::<InBackpack>().get(heart).map(|b| b.owner), Some(player));
+
+    sim.step(PlayerCommand::UseItem{ item: heart, target: None });
+    assert!(player_hp(&sim) >= 18);
+    assert!(!sim.ecs.is_alive(heart));
+}
+
+#[test]
+fn stepping_on_a_spike_trap_hurts() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let (dx, dy) = open_neighbour(&sim);
+    let pos = player_pos(&sim);
+    spawn(&mut sim, "Spike Trap", pos.x + dx, pos.y + dy);
+    let hp_before = player_hp(&sim);
+
+    sim.step(PlayerCommand::Move{ dx, dy });
+
+    assert_eq!(player_hp(&sim), hp_before - 2);
+}
+
+#[test]
+fn taking_the_stairs_reaches_depth_two() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    let stairs = {
+        let map = sim.ecs.fetch::<Map>();
+        let idx = map.tiles.iter().position(|t| *t == TileType::DownStairs).unwrap();
+        Point::new(idx as i32 % map.width, idx as i32 / map.width)
+    };
+    teleport_player(&mut sim, stairs);
+
+    sim.step(PlayerCommand::Descend);
+
+    assert_eq!(sim.depth(), 2);
+    assert_eq!(sim.run_state(), RunState::AwaitingInput);
+}
+
+/// Names and positions of everything standing on the current level
+fn level_contents(sim : &Simulation) -> Vec<(String, i32, i32)> {
+    let names = sim.ecs.read_storage::<Name>();
+    let positions = sim.ecs.read_storage::<Position>();
+    let players = sim.ecs.read_storage::<Player>();
+    let mut contents : Vec<(String, i32, i32)> = (&names, &positions, !&players).join()
+        .map(|(n, p, _)| (n.name.clone(), p.x, p.y))
+        .collect();
+    contents.sort();
+    contents
+}
+
+fn find_tile(sim : &Simulation, tile : TileType) -> Point {
+    let map = sim.ecs.fetch::<Map>();
+    let idx = map.tiles.iter().position(|t| *t == tile).unwrap();
+    Point::new(idx as i32 % map.width, idx as i32 / map.width)
+}
+
+#[test]
+fn climbing_back_up_restores_the_level() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    //?  Townsfolk wander about between visits, so only keep what stays put
+    let bystanders : Vec<Entity> = {
+        let entities = sim.ecs.entities();
+        let bystanders = sim.ecs.read_storage::<Bystander>();
+        (&entities, &bystanders).join().map(|(e, _b)| e).collect()
+    };
+    for bystander in bystanders { sim.ecs.delete_entity(bystander).unwrap(); }
+    sim.ecs.maintain();
+    sim.ecs.fetch_mut::<Map>().bloodstains.insert(100);
+    let tiles = sim.ecs.fetch::<Map>().tiles.clone();
+    let revealed = sim.ecs.fetch::<Map>().revealed_tiles.clone();
+    let contents = level_contents(&sim);
+    assert!(!contents.is_empty());
+
+    let down = find_tile(&sim, TileType::DownStairs);
+    teleport_player(&mut sim, down);
+    sim.step(PlayerCommand::Descend);
+    assert_eq!(sim.depth(), 2);
+    assert_eq!(player_pos(&sim), find_tile(&sim, TileType::UpStairs));
+
+    sim.step(PlayerCommand::Ascend);
+    assert_eq!(sim.depth(), 1);
+    assert_eq!(player_pos(&sim), down);
+    let map = sim.ecs.fetch::<Map>();
+    assert!(map.tiles == tiles);
+    assert!(map.bloodstains.contains(&100));
+    assert!(map.revealed_tiles.iter().zip(revealed.iter()).all(|(now, before)| *now || !*before));
+    drop(map);
+    assert_eq!(level_contents(&sim), contents);
+}
+
+/// Hits taken from a monster of `name` standing next to the player over ten
+/// waits, given how much damage each hit does
+fn hits_while_waiting(sim : &mut Simulation, name : &str, damage_per_hit : i32) -> i32 {
+    clear_monsters(sim);
+    let (dx, dy) = open_neighbour(sim);
+    let pos = player_pos(sim);
+    spawn(sim, name, pos.x + dx, pos.y + dy);
+    let hp_before = player_hp(sim);
+
+    sim.run(&[PlayerCommand::Wait; 10]);
+
+    (hp_before - player_hp(sim)) / damage_per_hit
+}
+
+#[test]
+fn slow_monsters_act_less_often() {
+    let zombie_hits = hits_while_waiting(&mut Simulation::new(SEED).unwrap(), "Zombie", 3);
+    let orc_hits = hits_while_waiting(&mut Simulation::new(SEED).unwrap(), "Orc", 2);
+
+    assert!(zombie_hits > 0);
+    assert!(zombie_hits < orc_hits);
+}
+
+#[test]
+fn quickening_draught_gives_the_player_extra_turns() {
+    let orc_hits = hits_while_waiting(&mut Simulation::new(SEED).unwrap(), "Orc", 2);
+
+    let mut sim = Simulation::new(SEED).unwrap();
+    let pos = player_pos(&sim);
+    let draught = spawn(&mut sim, "Quickening Draught", pos.x, pos.y);
+    sim.step(PlayerCommand::PickUp);
+    sim.step(PlayerCommand::UseItem{ item: draught, target: None });
+    assert!(sim.ecs.read_storage::<StatusEffects>().get(sim.player()).unwrap().has(StatusKind::Haste));
+    let hasted_orc_hits = hits_while_waiting(&mut sim, "Orc", 2);
+
+    assert!(hasted_orc_hits < orc_hits);
+}
+
+#[test]
+fn killing_a_goblin_earns_xp_and_can_level_up() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let player = sim.player();
+    let max_hp_before = sim.ecs.read_storage::<CombatStats>().get(player).unwrap().max_hp;
+    sim.ecs.write_storage::<Experience>().get_mut(player).unwrap().xp = 450;
+
+    let (dx, dy) = open_neighbour(&sim);
+    let pos = player_pos(&sim);
+    let goblin = spawn(&mut sim, "Goblin", pos.x + dx, pos.y + dy);
+    for _ in 0..20 {
+        sim.step(PlayerCommand::Move{ dx, dy });
+        if !sim.ecs.is_alive(goblin) { break; }
+    }
+    assert!(!sim.ecs.is_alive(goblin));
+
+    let experience = sim.ecs.read_storage::<Experience>().get(player).unwrap().clone();
+    assert_eq!(experience.level, 2);
+    assert_eq!(experience.xp, 50);
+    let stats = sim.ecs.read_storage::<CombatStats>().get(player).unwrap().clone();
+    assert_eq!(stats.max_hp, max_hp_before + 5);
+    assert_eq!(stats.hp, stats.max_hp);
+}
+
+fn afflict(sim : &mut Simulation, target : Entity, kind : StatusKind, turns : i32, magnitude : i32) {
+    StatusEffects::add_effect(&mut sim.ecs.write_storage::<StatusEffects>(), target, StatusEffect{ kind, turns, magnitude });
+}
+
+fn effect_on(sim : &Simulation, target : Entity, kind : StatusKind) -> Option<StatusEffect> {
+    sim.ecs.read_storage::<StatusEffects>().get(target)?.effects.iter().find(|e| e.kind == kind).copied()
+}
+
+#[test]
+fn repeated_effects_follow_their_stacking_rules() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    let player = sim.player();
+
+    afflict(&mut sim, player, StatusKind::Poison, 3, 2);
+    afflict(&mut sim, player, StatusKind::Poison, 5, 1);
+    assert_eq!(effect_on(&sim, player, StatusKind::Poison), Some(StatusEffect{ kind: StatusKind::Poison, turns: 5, magnitude: 3 }));
+
+    afflict(&mut sim, player, StatusKind::Bleeding, 3, 1);
+    afflict(&mut sim, player, StatusKind::Bleeding, 2, 1);
+    assert_eq!(effect_on(&sim, player, StatusKind::Bleeding).unwrap().turns, 5);
+
+    afflict(&mut sim, player, StatusKind::Confusion, 6, 0);
+    afflict(&mut sim, player, StatusKind::Confusion, 2, 0);
+    assert_eq!(effect_on(&sim, player, StatusKind::Confusion).unwrap().turns, 6);
+}
+
+#[test]
+fn poison_hurts_each_turn_then_wears_off() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let player = sim.player();
+    afflict(&mut sim, player, StatusKind::Poison, 3, 2);
+    let hp_before = player_hp(&sim);
+
+    sim.step(PlayerCommand::Wait);
+    assert_eq!(player_hp(&sim), hp_before - 2);
+
+    sim.run(&[PlayerCommand::Wait; 3]);
+    assert!(effect_on(&sim, player, StatusKind::Poison).is_none());
+    assert!(sim.ecs.fetch::<GameLog>().entries.iter().any(|e| e.text() == "You are no longer poisoned."));
+}
+
+#[test]
+fn confused_monsters_lose_their_turns() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let (dx, dy) = open_neighbour(&sim);
+    let pos = player_pos(&sim);
+    let orc = spawn(&mut sim, "Orc", pos.x + dx, pos.y + dy);
+    afflict(&mut sim, orc, StatusKind::Confusion, 3, 0);
+    let hp_before = player_hp(&sim);
+
+    sim.run(&[PlayerCommand::Wait; 2]);
+
+    assert_eq!(player_hp(&sim), hp_before);
+    assert!(effect_on(&sim, orc, StatusKind::Confusion).is_some());
+}
+
+/// A fresh, empty save directory for one test
+fn scratch_save_dir(name : &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("hellorust-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn saving_and_loading_a_slot_restores_the_game() {
+    let dir = scratch_save_dir("roundtrip");
+    let mut sim = Simulation::new(SEED).unwrap();
+    let (dx, dy) = open_neighbour(&sim);
+    sim.step(PlayerCommand::Move{ dx, dy });
+    let saved_pos = player_pos(&sim);
+    let saved_contents = level_contents(&sim);
+
+    saveload_system::save_game(&mut sim.ecs, &dir, 2).unwrap();
+    assert!(saveload_system::any_saves(&dir));
+    match saveload_system::slot_status(&dir, 2) {
+        saveload_system::SlotStatus::Saved(meta) => {
+            assert_eq!(meta.character, "Player");
+            assert_eq!(meta.depth, 1);
+            assert_eq!(meta.turns, 1);
+        }
+        _ => panic!("Slot 3 should hold the save"),
+    }
+    assert!(matches!(saveload_system::slot_status(&dir, 0), saveload_system::SlotStatus::Empty));
+
+    let mut other = Simulation::new(SEED + 1).unwrap();
+    saveload_system::load_game(&mut other.ecs, &dir, 2).unwrap();
+    assert_eq!(player_pos(&other), saved_pos);
+    assert_eq!(other.ecs.fetch::<TurnCounter>().turns, 1);
+    assert_eq!(level_contents(&other), saved_contents);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_or_corrupt_saves_fail_without_touching_the_world() {
+    let dir = scratch_save_dir("corrupt");
+    let mut sim = Simulation::new(SEED).unwrap();
+    let before = player_pos(&sim);
+
+    assert!(saveload_system::load_game(&mut sim.ecs, &dir, 0).is_err());
+
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("slot1.json"), "{ \"character\": \"Player\", ").unwrap();
+    assert!(matches!(saveload_system::slot_status(&dir, 0), saveload_system::SlotStatus::Unreadable(_)));
+    assert!(saveload_system::load_game(&mut sim.ecs, &dir, 0).is_err());
+
+    assert!(sim.ecs.is_alive(sim.player()));
+    assert_eq!(player_pos(&sim), before);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Rewrites the save in slot 1; the header comes first, then one storage
+/// per component
+fn edit_save(dir : &std::path::Path, edit : impl Fn(&mut Vec<serde_json::Value>)) {
+    let path = dir.join("slot1.json");
+    let data = std::fs::read_to_string(&path).unwrap();
+    let mut values : Vec<serde_json::Value> = serde_json::Deserializer::from_str(&data)
+        .into_iter().collect::<Result<_, _>>().unwrap();
+    edit(&mut values);
+    let text : Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    std::fs::write(&path, text.join("")).unwrap();
+}
+
+/// Rewrites the header of the save in slot 1
+fn edit_save_header(dir : &std::path::Path, edit : impl Fn(&mut serde_json::Value)) {
+    edit_save(dir, |values| edit(&mut values[0]));
+}
+
+#[test]
+fn version_one_saves_are_upgraded_when_loaded() {
+    let dir = scratch_save_dir("upgrade");
+    let mut sim = Simulation::new(SEED).unwrap();
+    let saved_contents = level_contents(&sim);
+    saveload_system::save_game(&mut sim.ecs, &dir, 0).unwrap();
+    //?  Version 1 predates doors and townsfolk, so their storages weren't written either
+    edit_save(&dir, |values| {
+        let names = values[0]["components"].as_array().unwrap().clone();
+        let mut idx = names.len();
+        while idx > 0 {
+            idx -= 1;
+            if ["BlocksVisibility", "Door", "OpensDoors", "Bystander", "Vendor"].iter().any(|added| names[idx] == *added) {
+                values.remove(idx + 1);
+            }
+        }
+        let header = values[0].as_object_mut().unwrap();
+        header.remove("version");
+        header.remove("components");
+    });
+
+    let mut other = Simulation::new(SEED + 1).unwrap();
+    saveload_system::load_game(&mut other.ecs, &dir, 0).unwrap();
+    assert_eq!(level_contents(&other), saved_contents);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn saves_from_newer_or_unknown_formats_are_refused() {
+    let dir = scratch_save_dir("newer");
+    let mut sim = Simulation::new(SEED).unwrap();
+    saveload_system::save_game(&mut sim.ecs, &dir, 0).unwrap();
+
+    edit_save_header(&dir, |header| header["version"] = (saveload_system::SAVE_VERSION + 1).into());
+    assert!(matches!(saveload_system::slot_status(&dir, 0), saveload_system::SlotStatus::Unreadable(_)));
+    let error = saveload_system::load_game(&mut sim.ecs, &dir, 0).unwrap_err();
+    assert!(error.contains("newer version"), "{}", error);
+
+    edit_save_header(&dir, |header| {
+        header["version"] = saveload_system::SAVE_VERSION.into();
+        header["components"][0] = "Teleporter".into();
+    });
+    let error = saveload_system::load_game(&mut sim.ecs, &dir, 0).unwrap_err();
+    assert!(error.contains("Teleporter"), "{}", error);
+    assert!(sim.ecs.is_alive(sim.player()));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn log_entries_carry_their_turn_and_colors_through_a_save() {
+    let dir = scratch_save_dir("log");
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    sim.step(PlayerCommand::Wait);
+    let pos = player_pos(&sim);
+    spawn(&mut sim, "Sword", pos.x, pos.y);
+    sim.step(PlayerCommand::PickUp);
+
+    let entry = sim.ecs.fetch::<GameLog>().entries.last().unwrap().clone();
+    assert_eq!(entry.text(), "You found a Sword.");
+    assert_eq!(entry.turn, 2);
+    assert!(entry.spans.iter().any(|span| span.color == LogColor::Item(Rarity::Rare) && span.text == "Sword"));
+
+    saveload_system::save_game(&mut sim.ecs, &dir, 0).unwrap();
+    let mut other = Simulation::new(SEED + 1).unwrap();
+    saveload_system::load_game(&mut other.ecs, &dir, 0).unwrap();
+    assert!(other.ecs.fetch::<GameLog>().entries == sim.ecs.fetch::<GameLog>().entries);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn deeper_levels_outgrow_the_screen_and_the_camera_follows() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    let stairs = find_tile(&sim, TileType::DownStairs);
+    teleport_player(&mut sim, stairs);
+    sim.step(PlayerCommand::Descend);
+
+    let (width, height) = { let map = sim.ecs.fetch::<Map>(); (map.width, map.height) };
+    assert!(width > camera::VIEW_WIDTH && height > camera::VIEW_HEIGHT);
+
+    for corner in [Point::new(1, 1), Point::new(width - 2, height - 2)] {
+        teleport_player(&mut sim, corner);
+        let view = camera::Viewport::for_player(&sim.ecs);
+        assert!(view.min_x >= 0 && view.min_x + camera::VIEW_WIDTH <= width);
+        assert!(view.min_y >= 0 && view.min_y + camera::VIEW_HEIGHT <= height);
+        let on_screen = view.world_to_screen(corner);
+        assert_eq!(view.screen_to_world(on_screen), Some(corner));
+    }
+}
+
+#[test]
+fn bumping_a_door_opens_it_then_lets_the_player_through() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    clear_monsters(&mut sim);
+    let (dx, dy) = open_neighbour(&sim);
+    let start = player_pos(&sim);
+    let door = spawn(&mut sim, "Door", start.x + dx, start.y + dy);
+    let door_idx = sim.ecs.fetch::<Map>().xy_idx(start.x + dx, start.y + dy);
+    assert!(sim.ecs.fetch::<Map>().is_opaque(door_idx));
+
+    sim.step(PlayerCommand::Move{ dx, dy });
+    assert_eq!(player_pos(&sim), start);
+    assert!(sim.ecs.read_storage::<Door>().get(door).unwrap().open);
+    assert!(!sim.ecs.fetch::<Map>().is_opaque(door_idx));
+
+    sim.step(PlayerCommand::Move{ dx, dy });
+    assert_eq!(player_pos(&sim), Point::new(start.x + dx, start.y + dy));
+}
+
+#[test]
+fn the_third_level_is_the_hand_drawn_goblin_warren() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    for _ in 0..2 {
+        let stairs = find_tile(&sim, TileType::DownStairs);
+        teleport_player(&mut sim, stairs);
+        sim.step(PlayerCommand::Descend);
+    }
+    assert_eq!(sim.depth(), 3);
+
+    let (width, height) = { let map = sim.ecs.fetch::<Map>(); (map.width, map.height) };
+    assert_eq!((width, height), (80, 43));
+    assert_eq!(player_pos(&sim), Point::new(5, 21));
+    assert_eq!(find_tile(&sim, TileType::DownStairs), Point::new(74, 21));
+
+    let contents = level_contents(&sim);
+    let goblins = contents.iter().filter(|(name, _, _)| name == "Goblin").count();
+    let doors = contents.iter().filter(|(name, _, _)| name == "Door").count();
+    assert_eq!((goblins, doors), (13, 5));
+}
+
+#[test]
+fn the_first_level_is_a_town_of_harmless_folk() {
+    let mut sim = Simulation::new(SEED).unwrap();
+    assert_eq!(sim.depth(), 1);
+    find_tile(&sim, TileType::DownStairs);
+    {
+        let monsters = sim.ecs.read_storage::<Monster>();
+        let vendors = sim.ecs.read_storage::<Vendor>();
+        let bystanders = sim.ecs.read_storage::<Bystander>();
+        assert_eq!(monsters.join().count(), 0);
+        assert_eq!(vendors.join().count(), 5);
+        assert!(bystanders.join().count() > 0);
+    }
+
+    //?  Bumping a vendor doesn't move the player or start a fight
+    let hp = player_hp(&sim);
+    let (dx, dy) = open_neighbour(&sim);
+    let start = player_pos(&sim);
+    let shopkeeper = spawn(&mut sim, "Shopkeeper", start.x + dx, start.y + dy);
+    sim.step(PlayerCommand::Move{ dx, dy });
+    assert_eq!(player_pos(&sim), start);
+    assert_eq!(sim.ecs.read_storage::<CombatStats>().get(shopkeeper).unwrap().hp, 8);
+    sim.ecs.delete_entity(shopkeeper).unwrap();
+    run_systems(&mut sim.ecs);
+
+    //?  Bumping a bystander trades places with them
+    let (dx, dy) = open_neighbour(&sim);
+    let townsperson = spawn(&mut sim, "Townsperson", start.x + dx, start.y + dy);
+    let their_pos = sim.ecs.read_storage::<Position>().get(townsperson).unwrap().clone();
+    let (dx, dy) = (their_pos.x - start.x, their_pos.y - start.y);
+    assert!(dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0));
+    sim.step(PlayerCommand::Move{ dx, dy });
+    assert_eq!(player_pos(&sim), Point::new(their_pos.x, their_pos.y));
+    let now = sim.ecs.read_storage::<Position>().get(townsperson).unwrap().clone();
+    assert!((now.x - start.x).abs() <= 1 && (now.y - start.y).abs() <= 1);
+    assert_eq!(sim.ecs.read_storage::<CombatStats>().get(townsperson).unwrap().hp, 8);
+    assert_eq!(player_hp(&sim), hp);
+}
+
+#[test]
+fn maps_export_as_text_ansi_and_png() {
+    use hellorust::map_export::{ export_map, ExportFormat, ExportOptions };
+    let sim = Simulation::new(SEED).unwrap();
+    let (width, height) = { let map = sim.ecs.fetch::<Map>(); (map.width, map.height) };
+
+    let text = String::from_utf8(export_map(&sim.ecs, ExportFormat::Text, ExportOptions::default()).unwrap()).unwrap();
+    let lines : Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), height as usize);
+    assert!(lines.iter().all(|line| line.chars().count() == width as usize && line.is_ascii()));
+    let stairs = find_tile(&sim, TileType::DownStairs);
+    assert_eq!(lines[stairs.y as usize].chars().nth(stairs.x as usize), Some('>'));
+    let pos = player_pos(&sim);
+    assert_eq!(lines[pos.y as usize].chars().nth(pos.x as usize), Some('@'));
+
+    let bare = String::from_utf8(export_map(&sim.ecs, ExportFormat::Text, ExportOptions{ entities : false, ..ExportOptions::default() }).unwrap()).unwrap();
+    assert_ne!(bare.lines().nth(pos.y as usize).unwrap().chars().nth(pos.x as usize), Some('@'));
+
+    let ansi = String::from_utf8(export_map(&sim.ecs, ExportFormat::Ansi, ExportOptions::default()).unwrap()).unwrap();
+    assert_eq!(ansi.lines().count(), height as usize);
+    assert!(ansi.lines().all(|line| line.ends_with("\x1b[0m")));
+
+    let png = export_map(&sim.ecs, ExportFormat::Png, ExportOptions::default()).unwrap();
+    let image = image::load_from_memory(&png).unwrap();
+    assert_eq!((image.width(), image.height()), (width as u32 * 8, height as u32 * 8));
+}
+
+#[test]
+fn the_export_command_prints_nothing_but_the_map() {
+    use hellorust::map_export::{ export_map, ExportFormat, ExportOptions };
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_hellorust"))
+        .args(["export-map", "--seed", "5", "--depth", "2", "--format", "text"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let mut sim = Simulation::new(5).unwrap();
+    goto_next_level(&mut sim.ecs);
+    run_systems(&mut sim.ecs);
+    let expected = export_map(&sim.ecs, ExportFormat::Text, ExportOptions::default()).unwrap();
+    assert!(output.stdout == expected, "stdout held more than the map:\n{}", String::from_utf8_lossy(&output.stdout));
+}